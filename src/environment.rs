@@ -23,11 +23,14 @@ impl Environment {
     ///
     /// # Errors
     ///
-    /// This function will error on memory allocation failure.
+    /// This function will return [`Error::EnvironmentAllocFailed`] if wasm3 fails to allocate
+    /// the environment.
+    ///
+    /// [`Error::EnvironmentAllocFailed`]: error/enum.Error.html#variant.EnvironmentAllocFailed
     #[inline]
     pub fn new() -> Result<Self> {
         unsafe { NonNull::new(ffi::m3_NewEnvironment()) }
-            .ok_or_else(Error::malloc_error)
+            .ok_or(Error::EnvironmentAllocFailed)
             .map(|raw| Environment(Rc::new(DropEnvironment(raw))))
     }
 
@@ -47,6 +50,24 @@ impl Environment {
         ParsedModule::parse(self, bytes)
     }
 
+    /// Parses a wasm module from raw bytes, rejecting modules larger than `max_len` bytes before
+    /// wasm3 gets to allocate anything for them.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::ModuleTooLarge`] if the module exceeds `max_len` bytes
+    /// and otherwise fail like [`Environment::parse_module`].
+    ///
+    /// [`Error::ModuleTooLarge`]: error/enum.Error.html#variant.ModuleTooLarge
+    /// [`Environment::parse_module`]: struct.Environment.html#method.parse_module
+    pub fn parse_module_with_limit(&self, bytes: &[u8], max_len: usize) -> Result<ParsedModule> {
+        if bytes.len() > max_len {
+            Err(Error::ModuleTooLarge)
+        } else {
+            ParsedModule::parse(self, bytes)
+        }
+    }
+
     #[inline]
     pub(crate) fn as_ptr(&self) -> ffi::IM3Environment {
         (self.0).0.as_ptr()
@@ -64,3 +85,14 @@ impl core::cmp::PartialEq for Environment {
 fn create_and_drop_env() {
     assert!(Environment::new().is_ok());
 }
+
+#[test]
+fn parse_module_with_limit() {
+    let env = Environment::new().expect("env alloc failure");
+    let empty = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+    assert!(env.parse_module_with_limit(&empty, 8).is_ok());
+    assert!(matches!(
+        env.parse_module_with_limit(&empty, 7),
+        Err(Error::ModuleTooLarge)
+    ));
+}
@@ -1,4 +1,6 @@
 //! Error related functionality of wasm3.
+use alloc::string::String;
+
 use core::cmp;
 use core::fmt;
 
@@ -24,6 +26,8 @@ pub enum Trap {
     IndirectCallTypeMismatch,
     /// Table index out of range
     TableIndexOutOfRange,
+    /// Null table element
+    TableElementIsNull,
     /// Exit
     Exit,
     /// Abort
@@ -45,6 +49,7 @@ impl Trap {
                 Trap::IntegerConversion => ffi::m3Err_trapIntegerConversion,
                 Trap::IndirectCallTypeMismatch => ffi::m3Err_trapIndirectCallTypeMismatch,
                 Trap::TableIndexOutOfRange => ffi::m3Err_trapTableIndexOutOfRange,
+                Trap::TableElementIsNull => ffi::m3Err_trapTableElementIsNull,
                 Trap::Exit => ffi::m3Err_trapExit,
                 Trap::Abort => ffi::m3Err_trapAbort,
                 Trap::Unreachable => ffi::m3Err_trapUnreachable,
@@ -54,6 +59,27 @@ impl Trap {
     }
 }
 
+impl Trap {
+    const ALL: [Trap; 11] = [
+        Trap::OutOfBoundsMemoryAccess,
+        Trap::DivisionByZero,
+        Trap::IntegerOverflow,
+        Trap::IntegerConversion,
+        Trap::IndirectCallTypeMismatch,
+        Trap::TableIndexOutOfRange,
+        Trap::TableElementIsNull,
+        Trap::Exit,
+        Trap::Abort,
+        Trap::Unreachable,
+        Trap::StackOverflow,
+    ];
+
+    /// Returns the trap the given error corresponds to, if it is one.
+    pub fn from_error(err: Wasm3Error) -> Option<Trap> {
+        Trap::ALL.iter().copied().find(|&trap| trap == err)
+    }
+}
+
 impl cmp::PartialEq<Wasm3Error> for Trap {
     fn eq(&self, &Wasm3Error(err): &Wasm3Error) -> bool {
         self.as_ptr() == err
@@ -79,6 +105,17 @@ impl Wasm3Error {
     }
 }
 
+impl Wasm3Error {
+    pub(crate) fn as_ptr(self) -> ffi::M3Result {
+        self.0
+    }
+
+    /// The message wasm3 associates with this error.
+    pub fn message(self) -> &'static str {
+        unsafe { cstr_to_str(self.0) }
+    }
+}
+
 impl cmp::PartialEq<Trap> for Wasm3Error {
     fn eq(&self, trap: &Trap) -> bool {
         trap.as_ptr() == self.0
@@ -98,6 +135,81 @@ impl fmt::Display for Wasm3Error {
     }
 }
 
+/// A report about a failed function call, see [`Runtime::last_trap`].
+///
+/// wasm3 does not record the call stack at the point of a trap, so the reported function is the
+/// one that was invoked from the host, not necessarily the one that trapped.
+///
+/// Errors raised while wasm3 lazily compiles a function during the call, like a call to an
+/// import that has not been linked, come with details such as the name of the missing import,
+/// see [`TrapReport::details`]. Traps carry no information beyond their kind.
+///
+/// [`TrapReport::details`]: struct.TrapReport.html#method.details
+/// [`Runtime::last_trap`]: ../struct.Runtime.html#method.last_trap
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrapReport {
+    pub(crate) error: Wasm3Error,
+    pub(crate) details: Option<String>,
+    pub(crate) function: String,
+    pub(crate) module: String,
+    pub(crate) runtime: Option<String>,
+}
+
+impl TrapReport {
+    /// The kind of trap that occurred, or `None` if the call failed for a different reason.
+    pub fn trap(&self) -> Option<Trap> {
+        Trap::from_error(self.error)
+    }
+
+    /// The raw error wasm3 returned.
+    pub fn error(&self) -> Wasm3Error {
+        self.error
+    }
+
+    /// The message describing the trap.
+    pub fn message(&self) -> &str {
+        self.error.message()
+    }
+
+    /// Additional details wasm3 reported about the error, if any.
+    pub fn details(&self) -> Option<&str> {
+        self.details.as_deref()
+    }
+
+    /// The name of the function that was called.
+    pub fn function_name(&self) -> &str {
+        &self.function
+    }
+
+    /// The name of the module the called function belongs to.
+    pub fn module_name(&self) -> &str {
+        &self.module
+    }
+
+    /// The name of the runtime the call happened in, if it had one at the time of the call.
+    pub fn runtime_name(&self) -> Option<&str> {
+        self.runtime.as_deref()
+    }
+}
+
+impl fmt::Display for TrapReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)?;
+        if let Some(details) = &self.details {
+            write!(f, ": {}", details)?;
+        }
+        write!(
+            f,
+            " in call to `{}` of module `{}`",
+            self.function, self.module
+        )?;
+        match &self.runtime {
+            Some(runtime) => write!(f, " in runtime `{}`", runtime),
+            None => Ok(()),
+        }
+    }
+}
+
 /// Error returned by wasm3-rs.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Error {
@@ -107,10 +219,37 @@ pub enum Error {
     InvalidFunctionSignature,
     /// The specified function could not be found.
     FunctionNotFound,
+    /// The specified global could not be found.
+    GlobalNotFound,
+    /// A global has been found but its type or mutability didn't match.
+    InvalidGlobalType,
     /// The specified module could not be found.
     ModuleNotFound,
     /// The modules environment did not match the runtime's environment.
     ModuleLoadEnvMismatch,
+    /// The module exceeded the maximum accepted size.
+    ModuleTooLarge,
+    /// No scratch region was set on the runtime.
+    NoScratchRegion,
+    /// A memory access was out of the bounds of a runtime's memory.
+    MemoryOutOfBounds,
+    /// The runtime's memory is guarded by a `MemoryGuard`, or a guard has already been used.
+    MemoryGuarded,
+    /// A string read from wasm memory was not valid UTF-8.
+    InvalidUtf8,
+    /// Formatting a value into a string failed.
+    Formatting,
+    /// A function of a module failed to validate, see `ParsedModule::validate`.
+    Validation {
+        /// The index of the offending function, counting imported functions.
+        function_index: u32,
+        /// The error wasm3 reported while compiling the function.
+        error: Wasm3Error,
+    },
+    /// wasm3 failed to allocate a new environment.
+    ///
+    /// wasm3 does not report anything beyond the failed allocation itself.
+    EnvironmentAllocFailed,
 }
 
 impl Error {
@@ -127,6 +266,12 @@ impl Error {
     }
 }
 
+impl From<fmt::Error> for Error {
+    fn from(_: fmt::Error) -> Self {
+        Error::Formatting
+    }
+}
+
 #[cfg(feature = "std")]
 impl std::error::Error for Error {}
 impl fmt::Display for Error {
@@ -137,10 +282,31 @@ impl fmt::Display for Error {
                 write!(f, "the found function had an unexpected signature")
             }
             Error::FunctionNotFound => write!(f, "the function could not be found"),
+            Error::GlobalNotFound => write!(f, "the global could not be found"),
+            Error::InvalidGlobalType => {
+                write!(f, "the found global had an unexpected type or mutability")
+            }
             Error::ModuleNotFound => write!(f, "the module could not be found"),
             Error::ModuleLoadEnvMismatch => {
                 write!(f, "the module and runtime environments were not the same")
             }
+            Error::ModuleTooLarge => write!(f, "the module exceeded the maximum size"),
+            Error::NoScratchRegion => write!(f, "the runtime has no scratch region"),
+            Error::MemoryOutOfBounds => write!(f, "the memory access was out of bounds"),
+            Error::MemoryGuarded => write!(f, "the runtime's memory is guarded"),
+            Error::InvalidUtf8 => write!(f, "the string was not valid utf-8"),
+            Error::Formatting => write!(f, "a formatting error occurred"),
+            Error::Validation {
+                function_index,
+                error,
+            } => write!(
+                f,
+                "function {} failed to validate: {}",
+                function_index, error
+            ),
+            Error::EnvironmentAllocFailed => {
+                write!(f, "the wasm3 environment could not be allocated")
+            }
         }
     }
 }
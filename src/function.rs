@@ -1,4 +1,6 @@
 use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 
 use core::cmp::{Eq, PartialEq};
 use core::hash::{Hash, Hasher};
@@ -7,8 +9,10 @@ use core::ptr::{self, NonNull};
 use core::slice;
 use core::str;
 
-use crate::error::{Error, Result};
+use crate::error::{Error, Result, TrapReport, Wasm3Error};
+use crate::memory::{Memory, MemoryGuard};
 use crate::runtime::Runtime;
+use crate::ty::{Signature, Value, ValueType};
 use crate::utils::cstr_to_str;
 use crate::wasm3_priv;
 use crate::{WasmArgs, WasmType};
@@ -64,6 +68,24 @@ impl<'cc> CallContext<'cc> {
     }
 }
 
+/// A clock used to measure the duration of function calls, see [`Function::call_timed`].
+///
+/// This allows timing calls on platforms without `std::time` by plugging in a platform timer.
+///
+/// [`Function::call_timed`]: struct.Function.html#method.call_timed
+pub trait Clock {
+    /// A point in time as reported by this clock.
+    type Instant;
+    /// The time that passed between two instants.
+    type Duration;
+
+    /// Returns the current point in time.
+    fn now(&self) -> Self::Instant;
+
+    /// Returns the time that passed between `start` and `end`.
+    fn elapsed(&self, start: Self::Instant, end: Self::Instant) -> Self::Duration;
+}
+
 // redefine of ffi::RawCall without the Option<T> around it
 /// Type of a raw host function for wasm3.
 pub type RawCall = unsafe extern "C" fn(
@@ -77,6 +99,11 @@ pub(crate) type NNM3Function = NonNull<ffi::M3Function>;
 /// A callable wasm3 function.
 /// This has a generic `call` function for up to 26 parameters emulating an overloading behaviour without having to resort to tuples.
 /// These are hidden to not pollute the documentation.
+///
+/// The signature of the wasm function is checked against `Args` and `Ret` once when the function
+/// is looked up, so a `Function` already serves as proof of a matching signature and calling it
+/// performs no further type checks. Keeping the `Function` around instead of looking it up again
+/// is therefore all that is needed to avoid repeated validation.
 #[derive(Debug, Clone)]
 pub struct Function<Args, Ret> {
     raw: NNM3Function,
@@ -97,11 +124,7 @@ impl<Args, Ret> Hash for Function<Args, Ret> {
     }
 }
 
-impl<Args, Ret> Function<Args, Ret>
-where
-    Args: WasmArgs,
-    Ret: WasmType,
-{
+impl<Args, Ret> Function<Args, Ret> {
     /// The name of the import module of this function.
     pub fn import_module_name(&self) -> &str {
         unsafe { cstr_to_str(self.raw.as_ref().import.moduleUtf8) }
@@ -111,39 +134,6 @@ where
     pub fn name(&self) -> &str {
         unsafe { cstr_to_str(self.raw.as_ref().name) }
     }
-}
-
-impl<Args, Ret> Function<Args, Ret>
-where
-    Args: WasmArgs,
-    Ret: WasmType,
-{
-    pub(crate) fn validate_sig(mut func: NNM3Function) -> Result<()> {
-        let &ffi::M3FuncType {
-            returnType: ret,
-            argTypes: ref args,
-            numArgs: num,
-            ..
-        } = unsafe { &*func.as_mut().funcType };
-        // argTypes is actually dynamically sized.
-        let args = unsafe { slice::from_raw_parts(args.as_ptr(), num as usize) };
-        match Ret::TYPE_INDEX == ret && Args::validate_types(args) {
-            true => Ok(()),
-            false => Err(Error::InvalidFunctionSignature),
-        }
-    }
-
-    #[inline]
-    pub(crate) fn from_raw(rt: Rc<Runtime>, raw: NNM3Function) -> Result<Self> {
-        Self::validate_sig(raw)?;
-        let this = Function {
-            raw,
-            rt,
-            _pd: PhantomData,
-        };
-        // make sure the function is compiled
-        this.compile()
-    }
 
     #[inline]
     pub(crate) fn compile(self) -> Result<Self> {
@@ -155,19 +145,84 @@ where
         Ok(self)
     }
 
-    fn call_impl(&self, args: Args) -> Result<Ret> {
+    /// The argument and return types of this function.
+    pub fn signature(&self) -> Signature {
+        Signature::from_func_type(self.func_type())
+    }
+
+    fn func_type(&self) -> &ffi::M3FuncType {
+        unsafe { &*self.raw.as_ref().funcType }
+    }
+
+    fn arg_types(&self) -> &[u8] {
+        let func_type = self.func_type();
+        // argTypes is actually dynamically sized.
+        unsafe { slice::from_raw_parts(func_type.argTypes.as_ptr(), func_type.numArgs as usize) }
+    }
+
+    // pushes arguments whose types have already been checked onto the stack
+    fn push_values(&self, args: &[Value]) {
         let stack = self.rt.stack_mut();
+        unsafe {
+            assert!(
+                args.len() * Value::SIZE_IN_SLOT_COUNT <= (&*stack).len(),
+                "wasm stack was too small"
+            );
+            let base = stack.cast::<ffi::m3slot_t>();
+            for (i, arg) in args.iter().enumerate() {
+                arg.push_on_stack(base.add(i * Value::SIZE_IN_SLOT_COUNT));
+            }
+        }
+    }
+
+    // runs the function with the arguments that have already been pushed onto the stack
+    fn execute(&self) -> Result<()> {
+        if self.rt.memory_guarded() {
+            return Err(Error::MemoryGuarded);
+        }
         let ret = unsafe {
-            args.push_on_stack(stack);
             Self::call_impl_(
                 self.raw.as_ref().compiled,
-                stack.cast(),
+                self.rt.stack_mut().cast(),
                 self.rt.mallocated(),
                 0,
                 0.0,
             )
         };
-        Error::from_ffi_res(ret.cast()).map(|()| unsafe { Ret::pop_from_stack(stack.cast()) })
+        Error::from_ffi_res(ret.cast()).map_err(|err| {
+            if let Error::Wasm3(error) = err {
+                self.rt.set_last_trap(self.trap_report(error));
+            }
+            err
+        })
+    }
+
+    fn trap_report(&self, error: Wasm3Error) -> TrapReport {
+        let module = unsafe { self.raw.as_ref().module };
+        // wasm3 only fills in the error info for errors it raises itself, not for traps, and it
+        // may still hold the info of an earlier, unrelated error
+        let info = unsafe {
+            let mut info = core::mem::MaybeUninit::<ffi::M3ErrorInfo>::zeroed();
+            ffi::m3_GetErrorInfo(self.rt.as_ptr(), info.as_mut_ptr());
+            info.assume_init()
+        };
+        let details = unsafe { cstr_to_str(info.message) };
+        TrapReport {
+            error,
+            details: if info.result == error.as_ptr() && !details.is_empty() {
+                Some(details.to_string())
+            } else {
+                None
+            },
+            function: self.name().to_string(),
+            module: if module.is_null() {
+                ""
+            } else {
+                unsafe { cstr_to_str((*module).name) }
+            }
+            .to_string(),
+            runtime: self.rt.name().map(|name| name.to_string()),
+        }
     }
 
     #[inline]
@@ -193,6 +248,278 @@ where
     }
 }
 
+impl<Args, Ret> Function<Args, Ret>
+where
+    Args: WasmArgs,
+    Ret: WasmType,
+{
+    pub(crate) fn validate_sig(mut func: NNM3Function) -> Result<()> {
+        let &ffi::M3FuncType {
+            returnType: ret,
+            argTypes: ref args,
+            numArgs: num,
+            ..
+        } = unsafe { &*func.as_mut().funcType };
+        // argTypes is actually dynamically sized.
+        let args = unsafe { slice::from_raw_parts(args.as_ptr(), num as usize) };
+        match Ret::TYPE_INDEX == ret && Args::validate_types(args) {
+            true => Ok(()),
+            false => Err(Error::InvalidFunctionSignature),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn from_raw(rt: Rc<Runtime>, raw: NNM3Function) -> Result<Self> {
+        Self::validate_sig(raw)?;
+        let this = Function {
+            raw,
+            rt,
+            _pd: PhantomData,
+        };
+        // make sure the function is compiled
+        this.compile()
+    }
+
+    /// Calls this function with the given arguments, measuring how long the call took with the
+    /// given clock.
+    ///
+    /// Unlike [`call`], the arguments are passed as a tuple, or a single value for unary functions.
+    ///
+    /// [`call`]: #method.call
+    pub fn call_timed<C: Clock>(&self, args: Args, clock: &C) -> Result<(Ret, C::Duration)> {
+        let start = clock.now();
+        let ret = self.call_impl(args)?;
+        let end = clock.now();
+        Ok((ret, clock.elapsed(start, end)))
+    }
+
+    /// Lazily calls this function once for every item of `inputs`, yielding the results in order.
+    ///
+    /// A failing call only yields its error, the remaining inputs are still processed.
+    pub fn call_stream<'f, I>(&'f self, inputs: I) -> impl Iterator<Item = Result<Ret>> + 'f
+    where
+        I: Iterator<Item = Args> + 'f,
+    {
+        inputs.map(move |args| self.call_impl(args))
+    }
+
+    /// Calls this function with its arguments read from guest memory starting at `args_ptr`.
+    ///
+    /// The arguments are expected to be stored back to back in little-endian order, each taking up
+    /// 4 or 8 bytes depending on its type, without any padding in between.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the arguments lie outside of the runtime's memory or if the
+    /// call traps.
+    pub fn call_from_memory(&self, args_ptr: u32) -> Result<Ret> {
+        let memory = unsafe { &*self.rt.memory() };
+        let mut ptr = args_ptr;
+        let args = self
+            .arg_types()
+            .iter()
+            .map(|&ty| {
+                // the signature has been validated against Args already
+                let ty = ValueType::from_type_index(ty).ok_or(Error::InvalidFunctionSignature)?;
+                let range = Memory::checked_range(ptr, ty.byte_size(), memory.len())?;
+                ptr = range.end as u32;
+                Ok(Value::from_le_bytes(ty, &memory[range]))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.push_values(&args);
+        self.execute()
+            .map(|()| unsafe { Ret::pop_from_stack(self.rt.stack_mut().cast()) })
+    }
+
+    fn call_impl(&self, args: Args) -> Result<Ret> {
+        let stack = self.rt.stack_mut();
+        unsafe { args.push_on_stack(stack) };
+        self.execute()
+            .map(|()| unsafe { Ret::pop_from_stack(stack.cast()) })
+    }
+}
+
+impl<Args> Function<Args, u64>
+where
+    Args: WasmArgs,
+{
+    /// Calls this function and returns a copy of the guest memory region described by the
+    /// returned value.
+    ///
+    /// As wasm3 does not support multiple return values, the region is expected to be packed into
+    /// the returned `u64` with the pointer in the lower and the length in the upper 32 bits.
+    /// The arguments are passed as a tuple, `()` for functions without parameters or a single
+    /// value for unary functions, for example `func.call_returning_bytes((ptr, len))`.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the call traps or if the region lies outside of the runtime's
+    /// memory.
+    pub fn call_returning_bytes(&self, args: Args) -> Result<Vec<u8>> {
+        let packed = self.call_impl(args)?;
+        let memory = unsafe { &*self.rt.memory() };
+        Memory::checked_range(packed as u32, (packed >> 32) as u32, memory.len())
+            .map(|range| memory[range].to_vec())
+    }
+
+    /// Like [`call_returning_bytes`], but additionally checks that the region holds valid UTF-8.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the call traps, if the region lies outside of the runtime's
+    /// memory or if it is not valid UTF-8.
+    ///
+    /// [`call_returning_bytes`]: #method.call_returning_bytes
+    pub fn call_returning_string(&self, args: Args) -> Result<String> {
+        String::from_utf8(self.call_returning_bytes(args)?).map_err(|_| Error::InvalidUtf8)
+    }
+
+    /// Like [`call_returning_string`], but borrows the string from the guest memory instead of
+    /// copying it.
+    ///
+    /// The guard keeps the memory from changing for as long as the string is borrowed, and can
+    /// only be used for a single call.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the guard has already been used, if the call traps, if the
+    /// region lies outside of the runtime's memory or if it is not valid UTF-8.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the guard belongs to a different runtime.
+    ///
+    /// [`call_returning_string`]: #method.call_returning_string
+    pub fn call_returning_str_borrowed<'m>(
+        &self,
+        args: Args,
+        mem_guard: &'m MemoryGuard,
+    ) -> Result<&'m str> {
+        assert!(
+            Rc::ptr_eq(&self.rt, mem_guard.runtime()),
+            "memory guard belongs to a different runtime"
+        );
+        if mem_guard.take_use() {
+            return Err(Error::MemoryGuarded);
+        }
+        self.rt.set_memory_guarded(false);
+        let packed = self.call_impl(args);
+        self.rt.set_memory_guarded(true);
+        let packed = packed?;
+        let memory = unsafe { &*mem_guard.runtime().memory() };
+        let range = Memory::checked_range(packed as u32, (packed >> 32) as u32, memory.len())?;
+        str::from_utf8(&memory[range]).map_err(|_| Error::InvalidUtf8)
+    }
+}
+
+impl<Ret> Function<(u32, u32), Ret>
+where
+    Ret: WasmType,
+{
+    /// Copies `bytes` into the runtime's scratch region and calls this function with the pointer
+    /// to and length of the copy, avoiding an allocation in the guest.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if no scratch region has been set with
+    /// [`Runtime::set_scratch_region`], if `bytes` does not fit into it, if the runtime's memory
+    /// is guarded by a [`MemoryGuard`] or if the call traps.
+    ///
+    /// [`Runtime::set_scratch_region`]: struct.Runtime.html#method.set_scratch_region
+    /// [`MemoryGuard`]: struct.MemoryGuard.html
+    pub fn call_with_bytes(&self, bytes: &[u8]) -> Result<Ret> {
+        if self.rt.memory_guarded() {
+            return Err(Error::MemoryGuarded);
+        }
+        let (offset, len) = self.rt.scratch_region().ok_or(Error::NoScratchRegion)?;
+        if bytes.len() > len as usize {
+            return Err(Error::MemoryOutOfBounds);
+        }
+        let memory = unsafe { &mut *self.rt.memory_mut() };
+        let range = Memory::checked_range(offset, bytes.len() as u32, memory.len())?;
+        memory[range].copy_from_slice(bytes);
+        self.call_impl((offset, bytes.len() as u32))
+    }
+}
+
+/// Something that can be called like a wasm function taking `Args` and returning `Ret`.
+///
+/// Host code that is generic over this trait can be tested with a closure standing in for the
+/// wasm function, as it is implemented for [`Function`] and for any
+/// `Fn(Args) -> Result<Ret>`.
+///
+/// [`Function`]: struct.Function.html
+pub trait WasmCallable<Args, Ret> {
+    /// Calls the function with the arguments passed as a tuple, or a single value for unary
+    /// functions.
+    fn invoke(&self, args: Args) -> Result<Ret>;
+}
+
+impl<Args, Ret> WasmCallable<Args, Ret> for Function<Args, Ret>
+where
+    Args: WasmArgs,
+    Ret: WasmType,
+{
+    fn invoke(&self, args: Args) -> Result<Ret> {
+        self.call_impl(args)
+    }
+}
+
+impl<Args, Ret, F> WasmCallable<Args, Ret> for F
+where
+    F: Fn(Args) -> Result<Ret>,
+{
+    fn invoke(&self, args: Args) -> Result<Ret> {
+        self(args)
+    }
+}
+
+/// Marker type for functions whose signature is only known at runtime, see
+/// [`Function::call_dynamic`].
+///
+/// [`Function::call_dynamic`]: struct.Function.html#method.call_dynamic
+#[derive(Debug, Clone, Copy)]
+pub enum Dynamic {}
+
+impl Function<Dynamic, Dynamic> {
+    #[inline]
+    pub(crate) fn from_raw_dynamic(rt: Rc<Runtime>, raw: NNM3Function) -> Result<Self> {
+        Function {
+            raw,
+            rt,
+            _pd: PhantomData,
+        }
+        .compile()
+    }
+
+    /// Calls this function with arguments whose types are only known at runtime.
+    ///
+    /// The arguments are written directly into the stack of the runtime, so no allocation takes
+    /// place for them. The stack is owned by the runtime and shared by all calls into it, which is
+    /// fine as runtimes can only be used from a single thread.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the arguments do not match the function's parameters or if the
+    /// call traps.
+    pub fn call_dynamic(&self, args: &[Value]) -> Result<Option<Value>> {
+        let types = self.arg_types();
+        if types.len() != args.len()
+            || args
+                .iter()
+                .zip(types)
+                .any(|(arg, &ty)| arg.value_type().type_index() != ty)
+        {
+            return Err(Error::InvalidFunctionSignature);
+        }
+        self.push_values(args);
+        self.execute()?;
+        let stack = self.rt.stack_mut();
+        Ok(ValueType::from_type_index(self.func_type().returnType)
+            .map(|ty| unsafe { Value::pop_from_stack(ty, stack.cast()) }))
+    }
+}
+
 macro_rules! func_call_impl {
     ($($types:ident),*) => { func_call_impl!(@rec [$($types,)*] []); };
     (@rec [] [$($types:ident,)*]) => { func_call_impl!(@do_impl $($types,)*); };
@@ -0,0 +1,68 @@
+use alloc::rc::Rc;
+
+use core::ptr::NonNull;
+
+use crate::error::{Error, Result};
+use crate::runtime::Runtime;
+use crate::ty::{Value, ValueType};
+
+/// A handle to a global of a loaded module, allowing the host to read and write its value.
+#[derive(Debug, Clone)]
+pub struct Global {
+    raw: NonNull<ffi::M3Global>,
+    // keeps the module owning the global alive
+    _rt: Rc<Runtime>,
+}
+
+impl Global {
+    pub(crate) fn from_raw(rt: Rc<Runtime>, raw: NonNull<ffi::M3Global>) -> Self {
+        Global { raw, _rt: rt }
+    }
+
+    /// The type of this global.
+    pub fn value_type(&self) -> ValueType {
+        // wasm3 only supports globals of the four number types
+        ValueType::from_type_index(unsafe { self.raw.as_ref().type_ })
+            .expect("global has an unsupported type")
+    }
+
+    /// Whether the guest may change the value of this global.
+    pub fn is_mutable(&self) -> bool {
+        unsafe { self.raw.as_ref().isMutable }
+    }
+
+    /// Returns the current value of this global.
+    pub fn get(&self) -> Value {
+        // wasm3 accesses the value union through a pointer of the global's type
+        let value = unsafe { &self.raw.as_ref().__bindgen_anon_1 };
+        unsafe {
+            match self.value_type() {
+                ValueType::I32 => Value::I32(*(value as *const _ as *const i32)),
+                ValueType::I64 => Value::I64(*(value as *const _ as *const i64)),
+                ValueType::F32 => Value::F32(*(value as *const _ as *const f32)),
+                ValueType::F64 => Value::F64(*(value as *const _ as *const f64)),
+            }
+        }
+    }
+
+    /// Sets the value of this global.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the value's type does not match the global's type.
+    pub fn set(&self, value: Value) -> Result<()> {
+        if value.value_type() != self.value_type() {
+            return Err(Error::InvalidGlobalType);
+        }
+        unsafe {
+            let slot = &mut (*self.raw.as_ptr()).__bindgen_anon_1;
+            match value {
+                Value::I32(val) => *(slot as *mut _ as *mut i32) = val,
+                Value::I64(val) => *(slot as *mut _ as *mut i64) = val,
+                Value::F32(val) => *(slot as *mut _ as *mut f32) = val,
+                Value::F64(val) => *(slot as *mut _ as *mut f64) = val,
+            }
+        }
+        Ok(())
+    }
+}
@@ -11,16 +11,40 @@ pub mod error;
 mod environment;
 pub use self::environment::Environment;
 mod function;
-pub use self::function::{CallContext, Function, RawCall};
+pub use self::function::{CallContext, Clock, Dynamic, Function, RawCall, WasmCallable};
+mod global;
+pub use self::global::Global;
 mod macros;
 pub use self::macros::*;
+mod memory;
+pub use self::memory::{Memory, MemoryGuard, MemoryInfo, MemoryMap};
 mod module;
-pub use self::module::{Module, ParsedModule};
+pub use self::module::{
+    DisallowedImport, ExportKind, LinkTarget, Module, ParsedModule, Producers, UnsupportedFeature,
+    WasiModuleKind,
+};
+mod parse;
 mod runtime;
 pub use self::runtime::Runtime;
+mod snapshot;
+pub use self::snapshot::{MemoryDiff, Snapshot};
+mod table;
+pub use self::table::Table;
 mod ty;
-pub use self::ty::{WasmArg, WasmArgs, WasmType};
+pub use self::ty::{Signature, Value, ValueType, WasmArg, WasmArgs, WasmType};
+#[cfg(feature = "derive")]
+pub use wasm3_derive::WasmType;
 mod utils;
+#[cfg(feature = "wasi")]
+mod wasi;
+#[cfg(feature = "wasi")]
+pub use self::wasi::{run_wasi, WasiConfig};
 pub use ffi as wasm3_sys;
 
 pub(crate) mod wasm3_priv;
+
+// used by the derive macros
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::ty::Newtype;
+}
@@ -59,3 +59,86 @@ macro_rules! make_func_wrapper {
         }
     };
 }
+
+/// A convenience macro for linking several closures to the imports of a module at once, see
+/// [`Module::link_closure`].
+///
+/// Every entry maps the name of an imported function to either a closure with annotated
+/// parameter types, whose signature is derived from those annotations, or to any other value
+/// accepted by [`Module::link_closure`]. The closures may take no parameters, may be `move`
+/// closures and may annotate their return type, but they do not receive the [`CallContext`].
+/// Linking stops at the first entry that fails, and the macro evaluates to its error.
+///
+/// # Example
+///
+/// ```ignore
+/// fn log(_: wasm3::CallContext, ptr: u32) {
+///     println!("{}", ptr);
+/// }
+///
+/// wasm3::link_functions!(module, "env", {
+///     "add" => |a: i32, b: i32| a + b,
+///     "now" => move || -> u64 { clock.now() },
+///     "log" => log,
+/// })
+/// .expect("Unable to link functions");
+/// ```
+///
+/// [`Module::link_closure`]: struct.Module.html#method.link_closure
+/// [`CallContext`]: struct.CallContext.html
+#[macro_export]
+macro_rules! link_functions {
+    ($module:expr, $module_name:expr, { $( $entries:tt )* }) => {{
+        let module: &mut $crate::Module = &mut $module;
+        let mut result: $crate::error::Result<()> = Ok(());
+        $crate::link_functions!(@entries module, result, $module_name, $( $entries )*);
+        result
+    }};
+    (@entries $module:ident, $result:ident, $module_name:expr, ) => {};
+    // `||` is a single token, so closures without parameters need arms of their own
+    (@entries $module:ident, $result:ident, $module_name:expr,
+        $name:literal => $( move )? || -> $ret:ty { $( $body:tt )* } $( , $( $rest:tt )* )?
+    ) => {
+        $crate::link_functions!(@link $module, $result, $module_name, $name, (),
+            move |_: $crate::CallContext<'_>, (): ()| -> $ret { $( $body )* }; $( $( $rest )* )?);
+    };
+    (@entries $module:ident, $result:ident, $module_name:expr,
+        $name:literal => $( move )? || $body:expr $( , $( $rest:tt )* )?
+    ) => {
+        $crate::link_functions!(@link $module, $result, $module_name, $name, (),
+            move |_: $crate::CallContext<'_>, (): ()| $body; $( $( $rest )* )?);
+    };
+    (@entries $module:ident, $result:ident, $module_name:expr,
+        $name:literal => $( move )? | $( $arg:ident: $ty:ty ),* $( , )? |
+            -> $ret:ty { $( $body:tt )* }
+        $( , $( $rest:tt )* )?
+    ) => {
+        $crate::link_functions!(@link $module, $result, $module_name, $name, ($( $ty, )*),
+            move |_: $crate::CallContext<'_>, ($( $arg, )*): ($( $ty, )*)| -> $ret { $( $body )* };
+            $( $( $rest )* )?);
+    };
+    (@entries $module:ident, $result:ident, $module_name:expr,
+        $name:literal => $( move )? | $( $arg:ident: $ty:ty ),* $( , )? | $body:expr
+        $( , $( $rest:tt )* )?
+    ) => {
+        $crate::link_functions!(@link $module, $result, $module_name, $name, ($( $ty, )*),
+            move |_: $crate::CallContext<'_>, ($( $arg, )*): ($( $ty, )*)| $body;
+            $( $( $rest )* )?);
+    };
+    (@entries $module:ident, $result:ident, $module_name:expr,
+        $name:literal => $func:expr $( , $( $rest:tt )* )?
+    ) => {
+        if $result.is_ok() {
+            $result = $module.link_closure($module_name, $name, $func);
+        }
+        $crate::link_functions!(@entries $module, $result, $module_name, $( $( $rest )* )?);
+    };
+    (@link $module:ident, $result:ident, $module_name:expr, $name:literal, $args:ty,
+        $closure:expr; $( $rest:tt )*
+    ) => {
+        if $result.is_ok() {
+            $result = $module.link_closure::<$args, _, _>($module_name, $name, $closure);
+        }
+        $crate::link_functions!(@entries $module, $result, $module_name, $( $rest )*);
+    };
+}
@@ -0,0 +1,166 @@
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+
+use core::cell::Cell;
+use core::ops::Range;
+
+use crate::error::{Error, Result};
+use crate::runtime::Runtime;
+
+/// Helpers for computing guest pointers into a runtime's linear memory.
+///
+/// Wasm pointers are `u32` offsets into memory, so plain arithmetic on them silently wraps around
+/// on overflow. These helpers detect the overflow instead and return
+/// [`Error::MemoryOutOfBounds`].
+///
+/// [`Error::MemoryOutOfBounds`]: error/enum.Error.html#variant.MemoryOutOfBounds
+#[derive(Debug)]
+pub enum Memory {}
+
+impl Memory {
+    /// Computes `base + offset`.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the result does not fit into a `u32`.
+    #[inline]
+    pub fn checked_ptr(base: u32, offset: u32) -> Result<u32> {
+        base.checked_add(offset).ok_or(Error::MemoryOutOfBounds)
+    }
+
+    /// Computes the pointer to the element at `index` of an array starting at `base` whose
+    /// elements are `size` bytes large, that is `base + index * size`.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the result does not fit into a `u32`.
+    #[inline]
+    pub fn checked_index(base: u32, index: u32, size: u32) -> Result<u32> {
+        index
+            .checked_mul(size)
+            .and_then(|offset| base.checked_add(offset))
+            .ok_or(Error::MemoryOutOfBounds)
+    }
+
+    /// Computes the byte range of the `len` bytes at `ptr`, checking that it lies within a memory
+    /// of `memory_len` bytes. The result can be used to index the slice returned by
+    /// [`Runtime::memory`].
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the end of the range overflows or lies past `memory_len`.
+    ///
+    /// [`Runtime::memory`]: struct.Runtime.html#method.memory
+    #[inline]
+    pub fn checked_range(ptr: u32, len: u32, memory_len: usize) -> Result<Range<usize>> {
+        let end = Self::checked_ptr(ptr, len)? as usize;
+        if end <= memory_len {
+            Ok(ptr as usize..end)
+        } else {
+            Err(Error::MemoryOutOfBounds)
+        }
+    }
+}
+
+/// The size and limits of a runtime's memory, see [`Runtime::memory_info`].
+///
+/// [`Runtime::memory_info`]: struct.Runtime.html#method.memory_info
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MemoryInfo {
+    /// The current size of the memory in bytes.
+    ///
+    /// This is less than `num_pages` pages if the runtime limits its memory usage.
+    pub size: usize,
+    /// The current number of 64 KiB wasm pages.
+    pub num_pages: u32,
+    /// The maximum number of pages the memory may grow to.
+    pub max_pages: u32,
+}
+
+/// An overview of the layout of a runtime's memory, see [`Runtime::memory_map`].
+///
+/// [`Runtime::memory_map`]: struct.Runtime.html#method.memory_map
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryMap {
+    /// The current size of the memory in bytes.
+    pub size: usize,
+    /// The regions initialized by the active data segments of all loaded modules, sorted by
+    /// their start.
+    pub data_segments: Vec<Range<u32>>,
+    /// The value of an exported `__data_end` global, marking the end of the static data.
+    pub data_end: Option<u32>,
+    /// The value of an exported `__heap_base` global, marking the start of the heap.
+    ///
+    /// For modules produced by LLVM, the stack grows down from here towards `data_end`.
+    pub heap_base: Option<u32>,
+}
+
+/// Keeps a runtime's memory from changing while borrowed results are alive, see
+/// [`Runtime::guard_memory`].
+///
+/// While the guard exists, calls into the runtime and [`Runtime::resize_memory`] fail with
+/// [`Error::MemoryGuarded`]. The guard itself can be used once, either for a single call through
+/// [`Function::call_returning_str_borrowed`] or to borrow the memory with
+/// [`Runtime::memory_cow`].
+///
+/// [`Runtime::guard_memory`]: struct.Runtime.html#method.guard_memory
+/// [`Runtime::resize_memory`]: struct.Runtime.html#method.resize_memory
+/// [`Error::MemoryGuarded`]: error/enum.Error.html#variant.MemoryGuarded
+/// [`Function::call_returning_str_borrowed`]: struct.Function.html#method.call_returning_str_borrowed
+/// [`Runtime::memory_cow`]: struct.Runtime.html#method.memory_cow
+#[derive(Debug)]
+pub struct MemoryGuard {
+    rt: Rc<Runtime>,
+    used: Cell<bool>,
+}
+
+impl MemoryGuard {
+    pub(crate) fn new(rt: Rc<Runtime>) -> Self {
+        MemoryGuard {
+            rt,
+            used: Cell::new(false),
+        }
+    }
+
+    pub(crate) fn runtime(&self) -> &Rc<Runtime> {
+        &self.rt
+    }
+
+    /// Marks the guard as used, returning whether it had been used before.
+    pub(crate) fn take_use(&self) -> bool {
+        self.used.replace(true)
+    }
+}
+
+impl Drop for MemoryGuard {
+    fn drop(&mut self) {
+        self.rt.set_memory_guarded(false);
+    }
+}
+
+#[test]
+fn checked_ptr_overflow() {
+    assert_eq!(Memory::checked_ptr(16, 4), Ok(20));
+    assert_eq!(
+        Memory::checked_ptr(u32::MAX - 1, 2),
+        Err(Error::MemoryOutOfBounds)
+    );
+}
+
+#[test]
+fn checked_index_overflow() {
+    assert_eq!(Memory::checked_index(8, 3, 4), Ok(20));
+    assert_eq!(
+        Memory::checked_index(0, 0x4000_0000, 4),
+        Err(Error::MemoryOutOfBounds)
+    );
+}
+
+#[test]
+fn checked_range_bounds() {
+    assert_eq!(Memory::checked_range(0, 16, 16), Ok(0..16));
+    assert_eq!(
+        Memory::checked_range(1, 16, 16),
+        Err(Error::MemoryOutOfBounds)
+    );
+}
@@ -1,18 +1,149 @@
 use alloc::boxed::Box;
 use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 
+use core::mem::ManuallyDrop;
 use core::ptr::{self, NonNull};
 use core::slice;
 
 use crate::environment::Environment;
-use crate::error::{Error, Result};
-use crate::function::{CallContext, Function, NNM3Function, RawCall};
+use crate::error::{Error, Result, TrappedResult};
+use crate::function::{CallContext, Dynamic, Function, NNM3Function, RawCall};
+use crate::global::Global;
+use crate::parse;
 use crate::runtime::Runtime;
+use crate::table::Table;
+use crate::ty::{Signature, Value, ValueType};
 use crate::utils::{cstr_to_str, eq_cstr_str};
 use crate::wasm3_priv;
 
+type RawCallEx = unsafe extern "C" fn(
+    runtime: ffi::IM3Runtime,
+    _sp: ffi::m3stack_t,
+    _mem: *mut cty::c_void,
+    cookie: *mut cty::c_void,
+) -> *const cty::c_void;
+
+/// The kind of an item exported or imported by a module.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ExportKind {
+    /// An exported function.
+    Function,
+    /// An exported table.
+    Table,
+    /// An exported memory.
+    Memory,
+    /// An exported global.
+    Global,
+}
+
+impl ExportKind {
+    fn from_byte(kind: u8) -> Option<Self> {
+        match kind {
+            0 => Some(ExportKind::Function),
+            1 => Some(ExportKind::Table),
+            2 => Some(ExportKind::Memory),
+            3 => Some(ExportKind::Global),
+            _ => None,
+        }
+    }
+}
+
+/// An import rejected by [`ParsedModule::verify_imports_allowed`].
+///
+/// [`ParsedModule::verify_imports_allowed`]: struct.ParsedModule.html#method.verify_imports_allowed
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DisallowedImport {
+    /// The name of the module the item is imported from.
+    pub module_name: String,
+    /// The name of the imported item.
+    pub name: String,
+    /// The kind of the imported item.
+    pub kind: ExportKind,
+}
+
+/// The kind of a WASI module, see [`Module::wasi_module_kind`].
+///
+/// [`Module::wasi_module_kind`]: struct.Module.html#method.wasi_module_kind
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WasiModuleKind {
+    /// A command module exporting `_start`, which is meant to be called once before the module is
+    /// discarded.
+    Command,
+    /// A reactor module exporting `_initialize`, which is meant to be called once before any of
+    /// its other exports.
+    Reactor,
+    /// A module exporting neither or both of `_start` and `_initialize`.
+    Unknown,
+}
+
+/// The host implementation an import gets linked to by an import resolver, see
+/// [`Module::resolve_imports`].
+///
+/// [`Module::resolve_imports`]: struct.Module.html#method.resolve_imports
+#[derive(Clone, Debug)]
+pub enum LinkTarget {
+    /// The default host function registered under the given names, see
+    /// [`Runtime::link_default_closure`].
+    ///
+    /// [`Runtime::link_default_closure`]: struct.Runtime.html#method.link_default_closure
+    Default {
+        /// The module name the default host function was registered with.
+        module_name: String,
+        /// The function name the default host function was registered with.
+        function_name: String,
+    },
+    /// A raw host function, which is only linked if the given signature matches the import's.
+    Raw {
+        /// The raw host function.
+        function: RawCall,
+        /// The signature the raw host function expects.
+        signature: Signature,
+    },
+}
+
+/// A post-MVP wasm feature that wasm3 cannot execute, see [`Runtime::can_load`].
+///
+/// [`Runtime::can_load`]: struct.Runtime.html#method.can_load
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UnsupportedFeature {
+    /// Blocks with parameters or multiple results.
+    MultiValue,
+    /// Bulk memory instructions like `memory.copy` and `memory.fill`.
+    BulkMemory,
+    /// Reference types and the table instructions that come with them.
+    ReferenceTypes,
+    /// 128 bit SIMD.
+    Simd,
+    /// Shared memories and atomic instructions.
+    Threads,
+    /// Tail calls.
+    TailCall,
+    /// Exception handling.
+    ExceptionHandling,
+}
+
+/// The toolchain that produced a module, as recorded in its `producers` custom section, see
+/// [`ParsedModule::producers`].
+///
+/// Each list holds pairs of a name and a version, the version may be empty.
+///
+/// [`ParsedModule::producers`]: struct.ParsedModule.html#method.producers
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Producers {
+    /// The source languages the module was written in.
+    pub language: Vec<(String, String)>,
+    /// The tools that processed the module, like compilers and optimizers.
+    pub processed_by: Vec<(String, String)>,
+    /// The SDKs the module was built with.
+    pub sdk: Vec<(String, String)>,
+}
+
 /// A parsed module which can be loaded into a [`Runtime`].
 pub struct ParsedModule {
+    // wasm3 keeps pointers into the module bytes, so they have to outlive the module
+    data: Box<[u8]>,
     raw: ffi::IM3Module,
     env: Environment,
 }
@@ -21,25 +152,201 @@ impl ParsedModule {
     /// Parses a wasm module from raw bytes.
     pub fn parse(env: &Environment, bytes: &[u8]) -> Result<Self> {
         assert!(bytes.len() <= !0u32 as usize);
+        let data: Box<[u8]> = bytes.into();
         let mut module = ptr::null_mut();
         let res = unsafe {
-            ffi::m3_ParseModule(
-                env.as_ptr(),
-                &mut module,
-                bytes.as_ptr(),
-                bytes.len() as u32,
-            )
+            ffi::m3_ParseModule(env.as_ptr(), &mut module, data.as_ptr(), data.len() as u32)
         };
         Error::from_ffi_res(res).map(|_| ParsedModule {
+            data,
             raw: module,
             env: env.clone(),
         })
     }
 
+    /// Returns the names and kinds of all exports of this module in declaration order.
+    pub fn export_names(&self) -> Vec<(String, ExportKind)> {
+        parse::exports(&self.data)
+            .filter_map(|(name, kind, _)| Some((name.to_string(), ExportKind::from_byte(kind)?)))
+            .collect()
+    }
+
+    /// Checks the bodies of all functions of this module by compiling them in a temporary runtime,
+    /// without loading this module anywhere.
+    ///
+    /// wasm3 does not implement the full validation algorithm of the specification, so this only
+    /// catches what its compiler checks for, like operand stack underflows, out of bounds
+    /// indices, unknown instructions and writes to immutable globals. Imports are not resolved,
+    /// and no guest code runs, so the start function is only compiled but not called.
+    ///
+    /// # Errors
+    ///
+    /// This function will error with [`Error::Validation`] for the first function that fails to
+    /// compile, or if creating the temporary runtime fails.
+    ///
+    /// [`Error::Validation`]: error/enum.Error.html#variant.Validation
+    pub fn validate(&self) -> Result<()> {
+        let rt = self.env.create_runtime(1024 * 64)?;
+        // compiling only needs the runtime for its code pages, so the copy is attached to it
+        // without loading it, which would initialize its memory and call its start function
+        let parsed = ParsedModule::parse(&self.env, &self.data)?;
+        unsafe { (*parsed.raw).runtime = rt.as_ptr() };
+        Module::from_raw(rt.clone(), parsed.raw).compile_all()
+    }
+
+    /// Returns the contents of this module's `producers` custom section, or `None` if it has none.
+    ///
+    /// Unknown fields are ignored.
+    pub fn producers(&self) -> Option<Producers> {
+        let payload = parse::custom_section(&self.data, "producers")?;
+        let mut producers = Producers::default();
+        for (field, values) in parse::producers(payload) {
+            let list = match field {
+                "language" => &mut producers.language,
+                "processed-by" => &mut producers.processed_by,
+                "sdk" => &mut producers.sdk,
+                _ => continue,
+            };
+            list.extend(
+                values
+                    .into_iter()
+                    .map(|(name, version)| (name.to_string(), version.to_string())),
+            );
+        }
+        Some(producers)
+    }
+
+    /// Checks that every import of this module, be it a function, table, memory or global, is
+    /// listed in the given allowlist of module and item names.
+    ///
+    /// # Errors
+    ///
+    /// This function returns all imports that are not in the allowlist.
+    pub fn verify_imports_allowed(
+        &self,
+        allowlist: &[(&str, &str)],
+    ) -> core::result::Result<(), Vec<DisallowedImport>> {
+        let disallowed: Vec<_> = parse::imports(&self.data)
+            .filter(|&(module, name, _)| !allowlist.contains(&(module, name)))
+            .filter_map(|(module, name, kind)| {
+                Some(DisallowedImport {
+                    module_name: module.to_string(),
+                    name: name.to_string(),
+                    kind: ExportKind::from_byte(kind)?,
+                })
+            })
+            .collect();
+        if disallowed.is_empty() {
+            Ok(())
+        } else {
+            Err(disallowed)
+        }
+    }
+
+    /// Returns a human-readable summary of this module, listing its memory, globals, imports,
+    /// exports with their signatures, the number of data and element segments and the names of its
+    /// custom sections.
+    ///
+    /// The format is meant for people and may change at any time.
+    pub fn debug_dump(&self) -> String {
+        let mut out = String::new();
+        self.write_dump(&mut out)
+            .expect("writing to a String does not fail");
+        out
+    }
+
+    fn write_dump(&self, out: &mut String) -> core::fmt::Result {
+        use core::fmt::Write;
+
+        let raw = unsafe { &*self.raw };
+        let functions = unsafe { slice_or_empty(raw.functions, raw.numFunctions) };
+        let globals = unsafe { slice_or_empty(raw.globals, raw.numGlobals) };
+        let signature = |func: &ffi::M3Function| {
+            if func.funcType.is_null() {
+                String::from("(?)")
+            } else {
+                Signature::from_func_type(unsafe { &*func.funcType }).to_string()
+            }
+        };
+        let global_type = |global: &ffi::M3Global| {
+            let ty = ValueType::from_type_index(global.type_)
+                .map(|ty| ty.to_string())
+                .unwrap_or_else(|| String::from("?"));
+            match global.isMutable {
+                true => alloc::format!("mut {}", ty),
+                false => ty,
+            }
+        };
+
+        if parse::section(&self.data, parse::SECTION_MEMORY).is_some() || raw.memoryImported {
+            write!(out, "memory: {} pages", raw.memoryInfo.initPages)?;
+            if raw.memoryInfo.maxPages != 0 {
+                write!(out, ", max {} pages", raw.memoryInfo.maxPages)?;
+            }
+            writeln!(out)?;
+        }
+        writeln!(out, "globals:")?;
+        for (index, global) in globals.iter().enumerate() {
+            writeln!(out, "  {}: {}", index, global_type(global))?;
+        }
+        writeln!(out, "imports:")?;
+        let (mut func_imports, mut global_imports) = (functions.iter(), globals.iter());
+        for (module, field, kind) in parse::imports(&self.data) {
+            write!(out, "  {}.{}: ", module, field)?;
+            match ExportKind::from_byte(kind) {
+                Some(ExportKind::Function) => match func_imports.next() {
+                    Some(func) => writeln!(out, "function {}", signature(func))?,
+                    None => writeln!(out, "function")?,
+                },
+                Some(ExportKind::Global) => match global_imports.next() {
+                    Some(global) => writeln!(out, "global {}", global_type(global))?,
+                    None => writeln!(out, "global")?,
+                },
+                Some(ExportKind::Table) => writeln!(out, "table")?,
+                Some(ExportKind::Memory) | None => writeln!(out, "memory")?,
+            }
+        }
+        writeln!(out, "exports:")?;
+        for (name, kind, index) in parse::exports(&self.data) {
+            write!(out, "  {}: ", name)?;
+            match ExportKind::from_byte(kind) {
+                Some(ExportKind::Function) => match functions.get(index as usize) {
+                    Some(func) => writeln!(out, "function {}", signature(func))?,
+                    None => writeln!(out, "function {}", index)?,
+                },
+                Some(ExportKind::Global) => match globals.get(index as usize) {
+                    Some(global) => writeln!(out, "global {}", global_type(global))?,
+                    None => writeln!(out, "global {}", index)?,
+                },
+                Some(ExportKind::Table) => writeln!(out, "table {}", index)?,
+                Some(ExportKind::Memory) | None => writeln!(out, "memory {}", index)?,
+            }
+        }
+        writeln!(out, "data segments: {}", raw.numDataSegments)?;
+        writeln!(out, "element segments: {}", raw.numElementSegments)?;
+        let customs: Vec<_> = parse::custom_section_names(&self.data).collect();
+        writeln!(out, "custom sections: {}", customs.join(", "))
+    }
+
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.data
+    }
+
     pub(crate) fn as_ptr(&self) -> ffi::IM3Module {
         self.raw
     }
 
+    /// Gives up ownership of the raw module, returning it together with the bytes it was parsed
+    /// from.
+    pub(crate) fn into_raw_parts(self) -> (ffi::IM3Module, Box<[u8]>) {
+        let mut this = ManuallyDrop::new(self);
+        // the environment is dropped here, the rest is handed to the caller
+        unsafe {
+            ptr::drop_in_place(&mut this.env);
+            (this.raw, ptr::read(&this.data))
+        }
+    }
+
     /// The environment this module was parsed in.
     pub fn environment(&self) -> &Environment {
         &self.env
@@ -53,6 +360,19 @@ impl Drop for ParsedModule {
 }
 
 /// A loaded module belonging to a specific runtime. Allows for linking and looking up functions.
+///
+/// A `Module` holds an `Rc` to its [`Runtime`] and is not tied to any borrow of it, so it can be
+/// stored in a struct next to, or instead of, the runtime itself. The runtime stays alive for as
+/// long as any of its modules or functions do.
+///
+/// ```ignore
+/// struct Plugin {
+///     module: wasm3::Module,
+///     update: wasm3::Function<f64, ()>,
+/// }
+/// ```
+///
+/// [`Runtime`]: struct.Runtime.html
 // needs no drop as loaded modules will be cleaned up by the runtime
 pub struct Module {
     raw: ffi::IM3Module,
@@ -100,6 +420,9 @@ impl Module {
     /// Links the given closure to the corresponding module and function name.
     /// This boxes the closure and therefor requires a heap allocation.
     ///
+    /// The closure may mutate its captured state across calls, this includes boxed
+    /// `dyn FnMut` closures.
+    ///
     /// # Errors
     ///
     /// This function will return an error in the following situations:
@@ -126,6 +449,185 @@ impl Module {
         Ok(())
     }
 
+    /// Links the given closure to the corresponding module and function name, handing it the raw
+    /// stack and memory pointers instead of decoded arguments.
+    ///
+    /// The closure reads its arguments from consecutive stack slots and writes its return value
+    /// into the first slot, just like a [`RawCall`] would, which avoids all marshalling overhead.
+    /// Returning a [`Trap`] aborts the wasm call with it. The closure is boxed and kept alive by
+    /// the runtime.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error in the following situations:
+    ///
+    /// * a memory allocation failed
+    /// * no function by the given name in the given module could be found
+    /// * the function has been found but the signature did not match
+    ///
+    /// [`RawCall`]: type.RawCall.html
+    /// [`Trap`]: error/enum.Trap.html
+    pub fn link_closure_raw<Args, Ret, F>(
+        &mut self,
+        module_name: &str,
+        function_name: &str,
+        closure: F,
+    ) -> Result<()>
+    where
+        Args: crate::WasmArgs,
+        Ret: crate::WasmType,
+        F: FnMut(ffi::m3stack_t, *mut cty::c_void) -> TrappedResult<()> + 'static,
+    {
+        let func = self.find_import_function(module_name, function_name)?;
+        Function::<Args, Ret>::validate_sig(func)?;
+        let mut closure = Box::pin(closure);
+        unsafe { self.link_raw_closure_impl(func, closure.as_mut().get_unchecked_mut()) }?;
+        self.rt.push_closure(closure);
+        Ok(())
+    }
+
+    /// Links the closure behind the given pointer, the closure has to be kept alive by the caller.
+    pub(crate) unsafe fn link_closure_ptr<Args, Ret, F>(
+        &self,
+        func: NNM3Function,
+        closure: *mut cty::c_void,
+    ) -> Result<()>
+    where
+        Args: crate::WasmArgs,
+        Ret: crate::WasmType,
+        F: for<'cc> FnMut(CallContext<'cc>, Args) -> Ret + 'static,
+    {
+        Function::<Args, Ret>::validate_sig(func)?;
+        self.link_closure_impl(func, closure.cast::<F>())
+    }
+
+    pub(crate) unsafe fn link_function_ptr<Args, Ret>(
+        &self,
+        func: NNM3Function,
+        f: *mut cty::c_void,
+    ) -> Result<()>
+    where
+        Args: crate::WasmArgs,
+        Ret: crate::WasmType,
+    {
+        Function::<Args, Ret>::validate_sig(func)?;
+        self.link_func_impl(func, core::mem::transmute::<*mut cty::c_void, RawCall>(f))
+    }
+
+    /// Links the function imports of this module that have not been linked yet to the targets
+    /// chosen by the given resolver.
+    ///
+    /// The resolver is called once with the module and field name of every unlinked import, and
+    /// may map it to a host implementation, for example to strip a version suffix from the name.
+    /// Imports for which it returns `None` are left unlinked. This is a one-shot pass, the
+    /// resolver is dropped afterwards, so it has no effect on modules loaded later on.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error in the following situations:
+    ///
+    /// * a memory allocation failed
+    /// * no default host function is registered under the names of a [`LinkTarget::Default`]
+    /// * the signature of a [`LinkTarget::Default`] or [`LinkTarget::Raw`] does not match the
+    ///   import's
+    ///
+    /// [`LinkTarget::Default`]: enum.LinkTarget.html#variant.Default
+    /// [`LinkTarget::Raw`]: enum.LinkTarget.html#variant.Raw
+    pub fn resolve_imports<F>(&mut self, mut resolver: F) -> Result<()>
+    where
+        F: FnMut(&str, &str) -> Option<LinkTarget>,
+    {
+        for func in unsafe { &mut *self.raw_functions() }.iter_mut() {
+            if func.import.moduleUtf8.is_null() || !func.compiled.is_null() {
+                continue;
+            }
+            let target = unsafe {
+                resolver(
+                    cstr_to_str(func.import.moduleUtf8),
+                    cstr_to_str(func.import.fieldUtf8),
+                )
+            };
+            let func = NonNull::from(func);
+            match target {
+                None => (),
+                Some(LinkTarget::Default {
+                    module_name,
+                    function_name,
+                }) => self
+                    .rt
+                    .link_default_to(self, func, &module_name, &function_name)?,
+                Some(LinkTarget::Raw {
+                    function,
+                    signature,
+                }) => {
+                    if Signature::from_func_type(unsafe { &*func.as_ref().funcType }) != signature {
+                        return Err(Error::InvalidFunctionSignature);
+                    }
+                    unsafe { self.link_func_impl(func, function) }?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Supplies the value of the global imported under the given module and field name.
+    ///
+    /// Globals are initialized when the module gets loaded, so globals of this module that are
+    /// initialized from the imported one keep the value they had before, unless
+    /// [`Module::reset_globals`] is called afterwards.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error in the following situations:
+    ///
+    /// * no global by the given name in the given module is imported
+    /// * the global has been found but its type does not match the value's type
+    ///
+    /// [`Module::reset_globals`]: struct.Module.html#method.reset_globals
+    pub fn link_global(&mut self, module_name: &str, field_name: &str, value: Value) -> Result<()> {
+        self.find_import_global(module_name, field_name)?.set(value)
+    }
+
+    /// Like [`Module::link_global`], but for a mutable global import whose value the host and guest
+    /// share. The returned handle can be used to read the value written by the guest and to
+    /// update it between calls.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error in the following situations:
+    ///
+    /// * no global by the given name in the given module is imported
+    /// * the global has been found but is not mutable or its type does not match the value's type
+    ///
+    /// [`Module::link_global`]: struct.Module.html#method.link_global
+    pub fn link_global_mut(
+        &mut self,
+        module_name: &str,
+        field_name: &str,
+        value: Value,
+    ) -> Result<Global> {
+        let global = self.find_import_global(module_name, field_name)?;
+        if !global.is_mutable() {
+            return Err(Error::InvalidGlobalType);
+        }
+        global.set(value)?;
+        Ok(global)
+    }
+
+    /// Looks up a global exported by this module under the given name.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if no global by the given name is exported.
+    pub fn find_global(&self, name: &str) -> Result<Global> {
+        parse::exports(self.rt.module_data(self.raw).unwrap_or(&[]))
+            .find(|&(export, kind, _)| {
+                export == name && ExportKind::from_byte(kind) == Some(ExportKind::Global)
+            })
+            .and_then(|(_, _, index)| self.global_at(index))
+            .ok_or(Error::GlobalNotFound)
+    }
+
     /// Looks up a function by the given name in this module.
     ///
     /// # Errors
@@ -140,23 +642,51 @@ impl Module {
         Args: crate::WasmArgs,
         Ret: crate::WasmType,
     {
-        let func = unsafe {
-            slice::from_raw_parts_mut(
-                if (*self.raw).functions.is_null() {
-                    NonNull::dangling().as_ptr()
-                } else {
-                    (*self.raw).functions
-                },
-                (*self.raw).numFunctions as usize,
-            )
-            .iter_mut()
-            .find(|func| eq_cstr_str(func.name, function_name))
-            .map(NonNull::from)
-            .ok_or(Error::FunctionNotFound)?
-        };
+        let func = self.find_raw_function(function_name)?;
         Function::from_raw(self.rt.clone(), func).and_then(Function::compile)
     }
 
+    /// Like [`Module::find_function`], but returns `Ok(None)` if this module contains no function
+    /// by the given name instead of an error, which allows probing for optional functions.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the function has been found but its signature did not
+    /// match, or on memory allocation failure.
+    ///
+    /// [`Module::find_function`]: struct.Module.html#method.find_function
+    pub fn try_find_function<Args, Ret>(
+        &self,
+        function_name: &str,
+    ) -> Result<Option<Function<Args, Ret>>>
+    where
+        Args: crate::WasmArgs,
+        Ret: crate::WasmType,
+    {
+        match self.find_function(function_name) {
+            Ok(func) => Ok(Some(func)),
+            Err(Error::FunctionNotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Looks up a function by the given name in this module without checking its signature
+    /// against static types. The returned function can be called with
+    /// [`Function::call_dynamic`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error in the following situations:
+    ///
+    /// * a memory allocation failed
+    /// * no function by the given name in the given module could be found
+    ///
+    /// [`Function::call_dynamic`]: struct.Function.html#method.call_dynamic
+    pub fn find_function_dynamic(&self, function_name: &str) -> Result<Function<Dynamic, Dynamic>> {
+        let func = self.find_raw_function(function_name)?;
+        Function::from_raw_dynamic(self.rt.clone(), func)
+    }
+
     /// Looks up a function by its index in this module.
     ///
     /// # Errors
@@ -187,11 +717,94 @@ impl Module {
         Function::from_raw(self.rt.clone(), func).and_then(Function::compile)
     }
 
+    /// Returns the names of all named functions of this module, including imported ones, in the
+    /// order of their indices.
+    pub fn function_names(&self) -> impl Iterator<Item = &str> + '_ {
+        unsafe { &*self.raw_functions() }
+            .iter()
+            .filter(|func| !func.name.is_null())
+            .map(|func| unsafe { cstr_to_str(func.name) })
+    }
+
+    /// Returns the names of all named functions of this module together with their signatures, in
+    /// the order of their indices.
+    pub fn function_signatures(&self) -> impl Iterator<Item = (String, Signature)> + '_ {
+        unsafe { &*self.raw_functions() }
+            .iter()
+            .filter(|func| !func.name.is_null() && !func.funcType.is_null())
+            .map(|func| unsafe {
+                (
+                    cstr_to_str(func.name).to_string(),
+                    Signature::from_func_type(&*func.funcType),
+                )
+            })
+    }
+
+    /// Returns the names of all named functions defined by this module that have been compiled, in
+    /// the order of their indices.
+    ///
+    /// As wasm3 compiles functions lazily on their first call, this serves as a coarse coverage
+    /// report telling which functions have run at least once. wasm3 does not count calls though,
+    /// and looking a function up from the host compiles it as well, even if it is never called.
+    pub fn compiled_function_names(&self) -> impl Iterator<Item = &str> + '_ {
+        unsafe { &*self.raw_functions() }
+            .iter()
+            .filter(|func| {
+                !func.name.is_null() && func.import.moduleUtf8.is_null() && !func.compiled.is_null()
+            })
+            .map(|func| unsafe { cstr_to_str(func.name) })
+    }
+
+    /// Returns the names of all named functions of this module sorted by name, see
+    /// [`Module::function_names`].
+    ///
+    /// [`Module::function_names`]: struct.Module.html#method.function_names
+    pub fn function_names_sorted(&self) -> Vec<&str> {
+        let mut names: Vec<_> = self.function_names().collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Returns the function table of this module, or `None` if it neither defines nor imports one.
+    pub fn table(&self) -> Option<Table> {
+        let data = self.rt.module_data(self.raw).unwrap_or(&[]);
+        let (_, max) = parse::table_limits(data)?;
+        Some(Table::new(unsafe { (*self.raw).table0Size }, max))
+    }
+
     /// The name of this module.
     pub fn name(&self) -> &str {
         unsafe { cstr_to_str((*self.raw).name) }
     }
 
+    /// Restores all globals defined by this module to the values of their initializers, leaving
+    /// memory untouched. Imported globals keep their current values.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if evaluating an initializer fails, in which case the globals
+    /// following it are left untouched.
+    pub fn reset_globals(&self) -> Result<()> {
+        unsafe { Error::from_ffi_res(wasm3_priv::InitGlobals(self.raw)) }
+    }
+
+    /// Determines whether this is a WASI command or reactor module from its exported functions.
+    pub fn wasi_module_kind(&self) -> WasiModuleKind {
+        let (mut start, mut initialize) = (false, false);
+        for (name, kind, _) in parse::exports(self.rt.module_data(self.raw).unwrap_or(&[])) {
+            match (name, ExportKind::from_byte(kind)) {
+                ("_start", Some(ExportKind::Function)) => start = true,
+                ("_initialize", Some(ExportKind::Function)) => initialize = true,
+                _ => (),
+            }
+        }
+        match (start, initialize) {
+            (true, false) => WasiModuleKind::Command,
+            (false, true) => WasiModuleKind::Reactor,
+            _ => WasiModuleKind::Unknown,
+        }
+    }
+
     /// Links wasi to this module.
     #[cfg(feature = "wasi")]
     pub fn link_wasi(&mut self) -> Result<()> {
@@ -204,6 +817,40 @@ impl Module {
         Module { raw, rt }
     }
 
+    // compiles every function, linking imports to a trapping stub first
+    fn compile_all(&self) -> Result<()> {
+        unsafe extern "C" fn unlinked(
+            _rt: ffi::IM3Runtime,
+            _sp: ffi::m3stack_t,
+            _mem: *mut cty::c_void,
+        ) -> *const cty::c_void {
+            ffi::m3Err_trapUnreachable.cast()
+        }
+
+        for (index, func) in unsafe { &mut *self.raw_functions() }.iter_mut().enumerate() {
+            let func = NonNull::from(func);
+            let res = unsafe {
+                if !func.as_ref().compiled.is_null() {
+                    continue;
+                } else if !func.as_ref().import.moduleUtf8.is_null() {
+                    self.link_func_impl(func, unlinked)
+                } else {
+                    Error::from_ffi_res(wasm3_priv::Compile_Function(func.as_ptr()))
+                }
+            };
+            match res {
+                Err(Error::Wasm3(error)) => {
+                    return Err(Error::Validation {
+                        function_index: index as u32,
+                        error,
+                    })
+                }
+                res => res?,
+            }
+        }
+        Ok(())
+    }
+
     unsafe fn link_func_impl(&self, mut m3_func: NNM3Function, func: RawCall) -> Result<()> {
         let page = wasm3_priv::AcquireCodePageWithCapacity(self.rt.as_ptr(), 2);
         if page.is_null() {
@@ -221,7 +868,7 @@ impl Module {
 
     unsafe fn link_closure_impl<Args, Ret, F>(
         &self,
-        mut m3_func: NNM3Function,
+        m3_func: NNM3Function,
         closure: *mut F,
     ) -> Result<()>
     where
@@ -256,6 +903,37 @@ impl Module {
             ffi::m3Err_none as _
         }
 
+        self.link_call_ex_impl(m3_func, _impl::<Args, Ret, F>, closure.cast())
+    }
+
+    unsafe fn link_raw_closure_impl<F>(&self, m3_func: NNM3Function, closure: *mut F) -> Result<()>
+    where
+        F: FnMut(ffi::m3stack_t, *mut cty::c_void) -> TrappedResult<()> + 'static,
+    {
+        unsafe extern "C" fn _impl<F>(
+            _runtime: ffi::IM3Runtime,
+            sp: ffi::m3stack_t,
+            mem: *mut cty::c_void,
+            closure: *mut cty::c_void,
+        ) -> *const cty::c_void
+        where
+            F: FnMut(ffi::m3stack_t, *mut cty::c_void) -> TrappedResult<()> + 'static,
+        {
+            match (&mut *closure.cast::<F>())(sp, mem) {
+                Ok(()) => ffi::m3Err_none as _,
+                Err(trap) => trap.as_ptr() as _,
+            }
+        }
+
+        self.link_call_ex_impl(m3_func, _impl::<F>, closure.cast())
+    }
+
+    unsafe fn link_call_ex_impl(
+        &self,
+        mut m3_func: NNM3Function,
+        call: RawCallEx,
+        cookie: *mut cty::c_void,
+    ) -> Result<()> {
         let page = wasm3_priv::AcquireCodePageWithCapacity(self.rt.as_ptr(), 3);
         if page.is_null() {
             Error::from_ffi_res(ffi::m3Err_mallocFailedCodePage)
@@ -263,15 +941,72 @@ impl Module {
             m3_func.as_mut().compiled = wasm3_priv::GetPagePC(page);
             m3_func.as_mut().module = self.raw;
             wasm3_priv::EmitWord_impl(page, crate::wasm3_priv::op_CallRawFunctionEx as _);
-            wasm3_priv::EmitWord_impl(page, _impl::<Args, Ret, F> as _);
-            wasm3_priv::EmitWord_impl(page, closure.cast());
+            wasm3_priv::EmitWord_impl(page, call as _);
+            wasm3_priv::EmitWord_impl(page, cookie);
 
             wasm3_priv::ReleaseCodePage(self.rt.as_ptr(), page);
             Ok(())
         }
     }
 
-    fn find_import_function(&self, module_name: &str, function_name: &str) -> Result<NNM3Function> {
+    fn raw_functions(&self) -> *mut [ffi::M3Function] {
+        unsafe {
+            ptr::slice_from_raw_parts_mut(
+                if (*self.raw).functions.is_null() {
+                    NonNull::dangling().as_ptr()
+                } else {
+                    (*self.raw).functions
+                },
+                (*self.raw).numFunctions as usize,
+            )
+        }
+    }
+
+    pub(crate) fn find_raw_function(&self, function_name: &str) -> Result<NNM3Function> {
+        unsafe {
+            (&mut *self.raw_functions())
+                .iter_mut()
+                .find(|func| eq_cstr_str(func.name, function_name))
+                .map(NonNull::from)
+                .ok_or(Error::FunctionNotFound)
+        }
+    }
+
+    pub(crate) fn global_at(&self, index: u32) -> Option<Global> {
+        unsafe {
+            if index < (*self.raw).numGlobals {
+                let global = NonNull::new_unchecked((*self.raw).globals.add(index as usize));
+                Some(Global::from_raw(self.rt.clone(), global))
+            } else {
+                None
+            }
+        }
+    }
+
+    fn find_import_global(&self, module_name: &str, field_name: &str) -> Result<Global> {
+        unsafe {
+            slice::from_raw_parts_mut(
+                if (*self.raw).globals.is_null() {
+                    NonNull::dangling().as_ptr()
+                } else {
+                    (*self.raw).globals
+                },
+                (*self.raw).numGlobals as usize,
+            )
+            .iter_mut()
+            .filter(|global| global.imported)
+            .filter(|global| eq_cstr_str(global.import.moduleUtf8, module_name))
+            .find(|global| eq_cstr_str(global.import.fieldUtf8, field_name))
+            .map(|global| Global::from_raw(self.rt.clone(), NonNull::from(global)))
+            .ok_or(Error::GlobalNotFound)
+        }
+    }
+
+    pub(crate) fn find_import_function(
+        &self,
+        module_name: &str,
+        function_name: &str,
+    ) -> Result<NNM3Function> {
         unsafe {
             slice::from_raw_parts_mut(
                 if (*self.raw).functions.is_null() {
@@ -290,6 +1025,14 @@ impl Module {
     }
 }
 
+unsafe fn slice_or_empty<'a, T>(ptr: *mut T, len: u32) -> &'a [T] {
+    if ptr.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr, len as usize)
+    }
+}
+
 #[test]
 fn module_parse() {
     let env = Environment::new().expect("env alloc failure");
@@ -302,3 +1045,45 @@ fn module_parse() {
     ];
     let _ = Module::parse(&env, &fib32[..]).unwrap();
 }
+
+#[test]
+fn module_export_names() {
+    let env = Environment::new().expect("env alloc failure");
+    let fib32 = [
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7f, 0x01,
+        0x7f, 0x03, 0x02, 0x01, 0x00, 0x07, 0x07, 0x01, 0x03, 0x66, 0x69, 0x62, 0x00, 0x00, 0x0a,
+        0x1f, 0x01, 0x1d, 0x00, 0x20, 0x00, 0x41, 0x02, 0x49, 0x04, 0x40, 0x20, 0x00, 0x0f, 0x0b,
+        0x20, 0x00, 0x41, 0x02, 0x6b, 0x10, 0x00, 0x20, 0x00, 0x41, 0x01, 0x6b, 0x10, 0x00, 0x6a,
+        0x0f, 0x0b,
+    ];
+    let module = Module::parse(&env, &fib32[..]).unwrap();
+    assert_eq!(
+        module.export_names(),
+        [("fib".to_string(), ExportKind::Function)]
+    );
+}
+
+#[test]
+fn module_producers() {
+    let env = Environment::new().expect("env alloc failure");
+    let producers = [
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x00, 0x42, 0x09, 0x70, 0x72, 0x6f, 0x64,
+        0x75, 0x63, 0x65, 0x72, 0x73, 0x02, 0x08, 0x6c, 0x61, 0x6e, 0x67, 0x75, 0x61, 0x67, 0x65,
+        0x01, 0x04, 0x52, 0x75, 0x73, 0x74, 0x00, 0x0c, 0x70, 0x72, 0x6f, 0x63, 0x65, 0x73, 0x73,
+        0x65, 0x64, 0x2d, 0x62, 0x79, 0x02, 0x05, 0x72, 0x75, 0x73, 0x74, 0x63, 0x06, 0x31, 0x2e,
+        0x35, 0x30, 0x2e, 0x30, 0x06, 0x77, 0x61, 0x6c, 0x72, 0x75, 0x73, 0x04, 0x30, 0x2e, 0x31,
+        0x39,
+    ];
+    let module = Module::parse(&env, &producers[..]).unwrap();
+    let pair = |name: &str, version: &str| (name.to_string(), version.to_string());
+    assert_eq!(
+        module.producers(),
+        Some(Producers {
+            language: alloc::vec![pair("Rust", "")],
+            processed_by: alloc::vec![pair("rustc", "1.50.0"), pair("walrus", "0.19")],
+            sdk: alloc::vec![],
+        })
+    );
+    let empty = Module::parse(&env, &producers[..8]).unwrap();
+    assert_eq!(empty.producers(), None);
+}
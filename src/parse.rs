@@ -0,0 +1,466 @@
+//! A minimal reader for the parts of the wasm binary format wasm3 does not keep around.
+//!
+//! Modules handed to this reader have already been parsed by wasm3, so malformed input is simply
+//! reported as `None` instead of a detailed error.
+
+use alloc::vec::Vec;
+
+use crate::module::UnsupportedFeature;
+
+pub(crate) const SECTION_CUSTOM: u8 = 0;
+pub(crate) const SECTION_IMPORT: u8 = 2;
+pub(crate) const SECTION_TABLE: u8 = 4;
+pub(crate) const SECTION_MEMORY: u8 = 5;
+pub(crate) const SECTION_EXPORT: u8 = 7;
+pub(crate) const SECTION_CODE: u8 = 10;
+pub(crate) const SECTION_DATA: u8 = 11;
+
+/// The minimum and optional maximum of a table or memory.
+pub(crate) type Limits = (u32, Option<u32>);
+
+#[derive(Clone)]
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub(crate) fn u8(&mut self) -> Option<u8> {
+        let (&byte, rest) = self.bytes.split_first()?;
+        self.bytes = rest;
+        Some(byte)
+    }
+
+    pub(crate) fn leb_u32(&mut self) -> Option<u32> {
+        let mut result = 0u32;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift == 28 && byte > 0x0F {
+                return None;
+            }
+            result |= u32::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Some(result);
+            }
+            shift += 7;
+        }
+    }
+
+    pub(crate) fn leb_i32(&mut self) -> Option<i32> {
+        let mut result = 0i32;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift == 28 && !matches!(byte & 0xF8, 0x00 | 0x78) {
+                return None;
+            }
+            result |= i32::from(byte & 0x7F) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 32 && byte & 0x40 != 0 {
+                    result |= !0 << shift;
+                }
+                return Some(result);
+            }
+        }
+    }
+
+    pub(crate) fn skip_leb(&mut self) -> Option<()> {
+        for _ in 0..10 {
+            if self.u8()? & 0x80 == 0 {
+                return Some(());
+            }
+        }
+        None
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.bytes.len() {
+            return None;
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(bytes)
+    }
+
+    pub(crate) fn name(&mut self) -> Option<&'a str> {
+        let len = self.leb_u32()? as usize;
+        core::str::from_utf8(self.bytes(len)?).ok()
+    }
+
+    pub(crate) fn limits(&mut self) -> Option<Limits> {
+        let flags = self.u8()?;
+        let min = self.leb_u32()?;
+        let max = if flags & 1 != 0 {
+            Some(self.leb_u32()?)
+        } else {
+            None
+        };
+        Some((min, max))
+    }
+}
+
+/// Returns an iterator over the id and payload of every section in the module.
+pub(crate) fn sections(module: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    // skip magic and version
+    let mut reader = Reader::new(module.get(8..).unwrap_or(&[]));
+    core::iter::from_fn(move || {
+        if reader.is_empty() {
+            return None;
+        }
+        let id = reader.u8()?;
+        let len = reader.leb_u32()? as usize;
+        Some((id, reader.bytes(len)?))
+    })
+}
+
+/// Returns the payload of the first section with the given id.
+pub(crate) fn section(module: &[u8], id: u8) -> Option<&[u8]> {
+    sections(module)
+        .find(|&(sid, _)| sid == id)
+        .map(|(_, payload)| payload)
+}
+
+/// Returns the payload of the first custom section with the given name, without the name.
+pub(crate) fn custom_section<'a>(module: &'a [u8], name: &str) -> Option<&'a [u8]> {
+    sections(module)
+        .filter(|&(id, _)| id == SECTION_CUSTOM)
+        .find_map(|(_, payload)| {
+            let mut reader = Reader::new(payload);
+            match reader.name()? == name {
+                true => Some(reader.bytes),
+                false => None,
+            }
+        })
+}
+
+/// Returns an iterator over the fields of a `producers` custom section payload, each with its
+/// list of name and version pairs.
+pub(crate) fn producers(payload: &[u8]) -> impl Iterator<Item = (&str, Vec<(&str, &str)>)> {
+    entries(payload, |reader| {
+        let field = reader.name()?;
+        let count = reader.leb_u32()?;
+        let values = (0..count)
+            .map(|_| Some((reader.name()?, reader.name()?)))
+            .collect::<Option<_>>()?;
+        Some((field, values))
+    })
+}
+
+/// Returns an iterator over the names of all custom sections.
+pub(crate) fn custom_section_names(module: &[u8]) -> impl Iterator<Item = &str> {
+    sections(module)
+        .filter(|&(id, _)| id == SECTION_CUSTOM)
+        .filter_map(|(_, payload)| Reader::new(payload).name())
+}
+
+/// Returns an iterator over the entries of a section that consists of a single vector, using the
+/// given function to read each entry.
+pub(crate) fn entries<'a, T, F>(payload: &'a [u8], mut read: F) -> impl Iterator<Item = T> + 'a
+where
+    F: FnMut(&mut Reader<'a>) -> Option<T> + 'a,
+{
+    let mut reader = Reader::new(payload);
+    let mut remaining = reader.leb_u32().unwrap_or(0);
+    core::iter::from_fn(move || {
+        if remaining == 0 {
+            return None;
+        }
+        remaining -= 1;
+        let entry = read(&mut reader);
+        if entry.is_none() {
+            remaining = 0;
+        }
+        entry
+    })
+}
+
+/// Returns an iterator over the name, kind and index of the module's exports.
+pub(crate) fn exports(module: &[u8]) -> impl Iterator<Item = (&str, u8, u32)> {
+    entries(section(module, SECTION_EXPORT).unwrap_or(&[]), |reader| {
+        Some((reader.name()?, reader.u8()?, reader.leb_u32()?))
+    })
+}
+
+/// The offset of an active data segment.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Offset {
+    Const(i32),
+    Global(u32),
+}
+
+fn offset_expr(reader: &mut Reader<'_>) -> Option<Offset> {
+    let offset = match reader.u8()? {
+        0x41 => Offset::Const(reader.leb_i32()?),
+        0x23 => Offset::Global(reader.leb_u32()?),
+        _ => return None,
+    };
+    match reader.u8()? {
+        0x0B => Some(offset),
+        _ => None,
+    }
+}
+
+/// Returns an iterator over the offset and length of the module's data segments, passive
+/// segments have no offset.
+pub(crate) fn data_segments(module: &[u8]) -> impl Iterator<Item = (Option<Offset>, u32)> + '_ {
+    entries(section(module, SECTION_DATA).unwrap_or(&[]), |reader| {
+        let offset = match reader.leb_u32()? {
+            0 => Some(offset_expr(reader)?),
+            1 => None,
+            2 => {
+                reader.leb_u32()?;
+                Some(offset_expr(reader)?)
+            }
+            _ => return None,
+        };
+        let len = reader.leb_u32()?;
+        reader.bytes(len as usize)?;
+        Some((offset, len))
+    })
+}
+
+/// Returns the limits of the module's first table, whether it is defined or imported.
+pub(crate) fn table_limits(module: &[u8]) -> Option<Limits> {
+    // imported tables come first in the table index space
+    if let Some(limits) = import_entries(module).find_map(|(_, _, _, table)| table) {
+        return Some(limits);
+    }
+    let mut reader = Reader::new(section(module, SECTION_TABLE)?);
+    if reader.leb_u32()? == 0 {
+        return None;
+    }
+    // element type
+    reader.u8()?;
+    reader.limits()
+}
+
+/// Reports the post-MVP features used by the module that wasm3 cannot execute.
+///
+/// wasm3 already rejects most of them while parsing, but function bodies are only compiled on
+/// their first call, so the code section is scanned here. Scanning a body stops at the first
+/// instruction that cannot be decoded.
+pub(crate) fn unsupported_features(module: &[u8], report: &mut dyn FnMut(UnsupportedFeature)) {
+    for (flags, _) in entries(section(module, SECTION_MEMORY).unwrap_or(&[]), |reader| {
+        let flags = reader.clone().u8()?;
+        reader.limits().map(|limits| (flags, limits))
+    }) {
+        if flags & 2 != 0 {
+            report(UnsupportedFeature::Threads);
+        }
+    }
+    for body in entries(section(module, SECTION_CODE).unwrap_or(&[]), |reader| {
+        let len = reader.leb_u32()? as usize;
+        reader.bytes(len)
+    }) {
+        scan_body(&mut Reader::new(body), report);
+    }
+}
+
+fn value_type(ty: u8, report: &mut dyn FnMut(UnsupportedFeature)) -> Option<()> {
+    match ty {
+        0x7C..=0x7F => (),
+        0x7B => report(UnsupportedFeature::Simd),
+        0x6F | 0x70 => report(UnsupportedFeature::ReferenceTypes),
+        _ => return None,
+    }
+    Some(())
+}
+
+fn scan_body(reader: &mut Reader<'_>, report: &mut dyn FnMut(UnsupportedFeature)) -> Option<()> {
+    use UnsupportedFeature::*;
+    for _ in 0..reader.leb_u32()? {
+        reader.leb_u32()?;
+        value_type(reader.u8()?, report)?;
+    }
+    while !reader.is_empty() {
+        match reader.u8()? {
+            // block, loop and if
+            0x02..=0x04 => match reader.clone().u8()? {
+                0x40 => {
+                    reader.u8()?;
+                }
+                ty @ 0x60..=0x7F => {
+                    reader.u8()?;
+                    value_type(ty, report)?;
+                }
+                // a type index, which allows for block parameters and multiple results
+                _ => {
+                    reader.leb_i32()?;
+                    report(MultiValue);
+                }
+            },
+            0x06..=0x09 | 0x18 | 0x19 => {
+                report(ExceptionHandling);
+                return None;
+            }
+            0x0C | 0x0D | 0x10 | 0x20..=0x24 | 0x41 | 0x42 => reader.skip_leb()?,
+            0x0E => {
+                for _ in 0..=reader.leb_u32()? {
+                    reader.skip_leb()?;
+                }
+            }
+            0x11 => {
+                reader.skip_leb()?;
+                if reader.leb_u32()? != 0 {
+                    report(ReferenceTypes);
+                }
+            }
+            0x12 => {
+                report(TailCall);
+                reader.skip_leb()?;
+            }
+            0x13 => {
+                report(TailCall);
+                reader.skip_leb()?;
+                reader.skip_leb()?;
+            }
+            0x1C => {
+                report(ReferenceTypes);
+                for _ in 0..reader.leb_u32()? {
+                    reader.u8()?;
+                }
+            }
+            0x25 | 0x26 | 0xD2 => {
+                report(ReferenceTypes);
+                reader.skip_leb()?;
+            }
+            0xD0 => {
+                report(ReferenceTypes);
+                reader.u8()?;
+            }
+            0xD1 => report(ReferenceTypes),
+            0x28..=0x3E => {
+                reader.skip_leb()?;
+                reader.skip_leb()?;
+            }
+            0x3F | 0x40 => {
+                reader.u8()?;
+            }
+            0x43 => {
+                reader.bytes(4)?;
+            }
+            0x44 => {
+                reader.bytes(8)?;
+            }
+            0xFC => match reader.leb_u32()? {
+                // saturating float to int conversions are supported
+                0..=7 => (),
+                // memory.init and table.init
+                8 | 12 => {
+                    report(BulkMemory);
+                    reader.skip_leb()?;
+                    reader.skip_leb()?;
+                }
+                // data.drop and elem.drop
+                9 | 13 => {
+                    report(BulkMemory);
+                    reader.skip_leb()?;
+                }
+                // memory.copy and table.copy
+                10 | 14 => {
+                    report(BulkMemory);
+                    reader.skip_leb()?;
+                    reader.skip_leb()?;
+                }
+                // memory.fill
+                11 => {
+                    report(BulkMemory);
+                    reader.skip_leb()?;
+                }
+                // table.grow, table.size and table.fill
+                15..=17 => {
+                    report(ReferenceTypes);
+                    reader.skip_leb()?;
+                }
+                _ => return None,
+            },
+            0xFD => {
+                report(Simd);
+                return None;
+            }
+            0xFE => {
+                report(Threads);
+                return None;
+            }
+            0x00 | 0x01 | 0x05 | 0x0B | 0x0F | 0x1A | 0x1B | 0x45..=0xC4 => (),
+            _ => return None,
+        }
+    }
+    Some(())
+}
+
+/// Returns an iterator over the module name, field name and kind of the module's imports.
+pub(crate) fn imports(module: &[u8]) -> impl Iterator<Item = (&str, &str, u8)> {
+    import_entries(module).map(|(module, field, kind, _)| (module, field, kind))
+}
+
+/// Like [`imports`], but also yields the limits of imported tables.
+fn import_entries(module: &[u8]) -> impl Iterator<Item = (&str, &str, u8, Option<Limits>)> {
+    entries(section(module, SECTION_IMPORT).unwrap_or(&[]), |reader| {
+        let module = reader.name()?;
+        let field = reader.name()?;
+        let kind = reader.u8()?;
+        let mut table = None;
+        match kind {
+            // function: type index
+            0 => {
+                reader.leb_u32()?;
+            }
+            // table: element type and limits
+            1 => {
+                reader.u8()?;
+                table = Some(reader.limits()?);
+            }
+            // memory: limits
+            2 => {
+                reader.limits()?;
+            }
+            // global: value type and mutability
+            3 => {
+                reader.bytes(2)?;
+            }
+            _ => return None,
+        }
+        Some((module, field, kind, table))
+    })
+}
+
+#[test]
+fn reader_leb_u32() {
+    assert_eq!(Reader::new(&[0xE5, 0x8E, 0x26]).leb_u32(), Some(624_485));
+}
+
+#[test]
+fn reader_leb_u32_overflow() {
+    assert_eq!(Reader::new(&[0xFF, 0xFF, 0xFF, 0xFF, 0x1F]).leb_u32(), None);
+}
+
+#[test]
+fn reader_leb_i32() {
+    assert_eq!(Reader::new(&[0xC0, 0xBB, 0x78]).leb_i32(), Some(-123_456));
+    assert_eq!(Reader::new(&[0xE4, 0x00]).leb_i32(), Some(100));
+}
+
+#[test]
+fn reader_leb_u32_underrun() {
+    assert_eq!(Reader::new(&[0x80]).leb_u32(), None);
+}
+
+#[test]
+fn table_limits_imported() {
+    let module = [
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x02, 0x0a, 0x01, 0x01, 0x65, 0x01, 0x74,
+        0x01, 0x70, 0x01, 0x02, 0x05,
+    ];
+    assert_eq!(table_limits(&module), Some((2, Some(5))));
+    assert_eq!(table_limits(&module[..8]), None);
+}
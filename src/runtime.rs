@@ -1,27 +1,72 @@
+use alloc::borrow::Cow;
 use alloc::boxed::Box;
 use alloc::rc::Rc;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
-use core::cell::UnsafeCell;
+use core::cell::{Cell, RefCell, UnsafeCell};
+use core::fmt;
 use core::mem;
 use core::pin::Pin;
 use core::ptr::{self, NonNull};
 
 use crate::environment::Environment;
-use crate::error::{Error, Result};
-use crate::function::Function;
-use crate::module::{Module, ParsedModule};
+use crate::error::{Error, Result, TrapReport};
+use crate::function::{CallContext, Function, NNM3Function, RawCall};
+use crate::memory::{MemoryGuard, MemoryInfo, MemoryMap};
+use crate::module::{Module, ParsedModule, UnsupportedFeature};
+use crate::parse::{self, Offset};
+use crate::snapshot::{MemoryDiff, Snapshot};
+use crate::ty::Value;
 use crate::utils::eq_cstr_str;
 
 type PinnedAnyClosure = Pin<Box<dyn core::any::Any + 'static>>;
 
+type DefaultLinker = unsafe fn(&Module, NNM3Function, *mut cty::c_void) -> Result<()>;
+
+// a host function that gets linked into every module loaded after it has been registered
+struct DefaultLink {
+    module_name: String,
+    function_name: String,
+    linker: DefaultLinker,
+    data: *mut cty::c_void,
+}
+
+impl fmt::Debug for DefaultLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DefaultLink")
+            .field("module_name", &self.module_name)
+            .field("function_name", &self.function_name)
+            .finish()
+    }
+}
+
+struct TrapHandler(Box<dyn FnMut(&TrapReport)>);
+
+impl fmt::Debug for TrapHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TrapHandler")
+    }
+}
+
 /// A runtime context for wasm3 modules.
 #[derive(Debug)]
 pub struct Runtime {
     raw: NonNull<ffi::M3Runtime>,
+    name: RefCell<Option<Rc<str>>>,
     environment: Environment,
     // holds all linked closures so that they properly get disposed of when runtime drops
     closure_store: UnsafeCell<Vec<PinnedAnyClosure>>,
+    last_trap: RefCell<Option<TrapReport>>,
+    trap_handler: RefCell<Option<TrapHandler>>,
+    scratch_region: Cell<Option<(u32, u32)>>,
+    memory_guarded: Cell<bool>,
+    default_links: RefCell<Vec<DefaultLink>>,
+    // caches the results of find_function, as it has to walk all modules otherwise
+    #[cfg(feature = "std")]
+    function_cache: RefCell<std::collections::HashMap<String, NNM3Function>>,
+    // keeps the bytes of loaded modules alive as wasm3 references them
+    module_data: UnsafeCell<Vec<(ffi::IM3Module, Box<[u8]>)>>,
 }
 
 impl Runtime {
@@ -41,47 +86,226 @@ impl Runtime {
         .ok_or_else(Error::malloc_error)
         .map(|raw| Runtime {
             raw,
+            name: RefCell::new(None),
             environment: environment.clone(),
             closure_store: UnsafeCell::new(Vec::new()),
+            last_trap: RefCell::new(None),
+            trap_handler: RefCell::new(None),
+            scratch_region: Cell::new(None),
+            memory_guarded: Cell::new(false),
+            default_links: RefCell::new(Vec::new()),
+            #[cfg(feature = "std")]
+            function_cache: RefCell::new(std::collections::HashMap::new()),
+            module_data: UnsafeCell::new(Vec::new()),
         })
     }
 
     /// Parses and loads a module from bytes.
+    ///
+    /// See [`Runtime::load_module`] for possible error cases.
+    ///
+    /// [`Runtime::load_module`]: struct.Runtime.html#method.load_module
     pub fn parse_and_load_module(self: &Rc<Self>, bytes: &[u8]) -> Result<Module> {
+        if self.memory_guarded() {
+            return Err(Error::MemoryGuarded);
+        }
         Module::parse(&self.environment, bytes).and_then(|module| self.load_module(module))
     }
 
+    /// Checks whether the given module only uses wasm features this runtime can execute.
+    ///
+    /// wasm3 compiles functions lazily, so a module using unsupported instructions may load
+    /// successfully and only fail once such a function is called. This scans the entire module up
+    /// front instead.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the unsupported features used by the module, without duplicates.
+    pub fn can_load(
+        &self,
+        parsed: &ParsedModule,
+    ) -> core::result::Result<(), Vec<UnsupportedFeature>> {
+        let mut unsupported = Vec::new();
+        parse::unsupported_features(parsed.data(), &mut |feature| unsupported.push(feature));
+        unsupported.sort_unstable();
+        unsupported.dedup();
+        if unsupported.is_empty() {
+            Ok(())
+        } else {
+            Err(unsupported)
+        }
+    }
+
     /// Loads a parsed module returning the module if unsuccessful.
     ///
+    /// The module's imports are linked against the runtime's default host functions before it
+    /// gets loaded, see [`Runtime::link_default_closure`]. Imports whose signature differs from
+    /// the default host function registered under their name are left unlinked, so that they can
+    /// still be linked explicitly.
+    ///
     /// # Errors
     ///
-    /// This function will error if the module's environment differs from the one this runtime uses.
+    /// This function will error if the module's environment differs from the one this runtime uses
+    /// or if the runtime's memory is currently guarded by a [`MemoryGuard`], as loading a module
+    /// may resize the memory and runs its start function.
+    ///
+    /// [`Runtime::link_default_closure`]: struct.Runtime.html#method.link_default_closure
+    /// [`MemoryGuard`]: struct.MemoryGuard.html
     pub fn load_module(self: &Rc<Self>, module: ParsedModule) -> Result<Module> {
         if &self.environment != module.environment() {
             Err(Error::ModuleLoadEnvMismatch)
+        } else if self.memory_guarded() {
+            Err(Error::MemoryGuarded)
         } else {
+            let handle = Module::from_raw(self.clone(), module.as_ptr());
+            for link in self.default_links.borrow().iter() {
+                match handle.find_import_function(&link.module_name, &link.function_name) {
+                    Ok(func) => match unsafe { (link.linker)(&handle, func, link.data) } {
+                        Ok(()) | Err(Error::InvalidFunctionSignature) => (),
+                        Err(e) => return Err(e),
+                    },
+                    Err(Error::FunctionNotFound) => (),
+                    Err(e) => return Err(e),
+                }
+            }
             Error::from_ffi_res(unsafe { ffi::m3_LoadModule(self.raw.as_ptr(), module.as_ptr()) })?;
-            let raw = module.as_ptr();
-            mem::forget(module);
-            Ok(Module::from_raw(self.clone(), raw))
+            let (raw, data) = module.into_raw_parts();
+            unsafe { (*self.module_data.get()).push((raw, data)) };
+            // the new module may shadow previously found functions
+            #[cfg(feature = "std")]
+            self.function_cache.borrow_mut().clear();
+            Ok(handle)
         }
     }
 
+    /// Registers a closure as a default host function of this runtime.
+    ///
+    /// Default host functions are linked into every module loaded afterwards that imports a function
+    /// by the given module and function name. Linking a function to a loaded module via
+    /// [`Module::link_closure`] or [`Module::link_function`] overrides the default, as long as
+    /// this happens before any function calling the import has been called.
+    ///
+    /// [`Module::link_closure`]: ../module/struct.Module.html#method.link_closure
+    /// [`Module::link_function`]: ../module/struct.Module.html#method.link_function
+    pub fn link_default_closure<Args, Ret, F>(
+        &self,
+        module_name: &str,
+        function_name: &str,
+        closure: F,
+    ) where
+        Args: crate::WasmArgs,
+        Ret: crate::WasmType,
+        F: for<'cc> FnMut(CallContext<'cc>, Args) -> Ret + 'static,
+    {
+        let mut closure = Box::pin(closure);
+        let data = unsafe { closure.as_mut().get_unchecked_mut() } as *mut F;
+        self.push_closure(closure);
+        self.default_links.borrow_mut().push(DefaultLink {
+            module_name: module_name.to_string(),
+            function_name: function_name.to_string(),
+            linker: Module::link_closure_ptr::<Args, Ret, F>,
+            data: data.cast(),
+        });
+    }
+
+    // links the default host function registered under the given names to func
+    pub(crate) fn link_default_to(
+        &self,
+        module: &Module,
+        func: NNM3Function,
+        module_name: &str,
+        function_name: &str,
+    ) -> Result<()> {
+        let links = self.default_links.borrow();
+        let link = links
+            .iter()
+            .find(|link| link.module_name == module_name && link.function_name == function_name)
+            .ok_or(Error::FunctionNotFound)?;
+        unsafe { (link.linker)(module, func, link.data) }
+    }
+
+    /// Registers a raw function as a default host function of this runtime.
+    ///
+    /// See [`Runtime::link_default_closure`] for how default host functions are linked.
+    ///
+    /// [`Runtime::link_default_closure`]: struct.Runtime.html#method.link_default_closure
+    pub fn link_default_function<Args, Ret>(
+        &self,
+        module_name: &str,
+        function_name: &str,
+        f: RawCall,
+    ) where
+        Args: crate::WasmArgs,
+        Ret: crate::WasmType,
+    {
+        self.default_links.borrow_mut().push(DefaultLink {
+            module_name: module_name.to_string(),
+            function_name: function_name.to_string(),
+            linker: Module::link_function_ptr::<Args, Ret>,
+            data: f as *mut cty::c_void,
+        });
+    }
+
     /// Looks up a function by the given name in the loaded modules of this runtime.
     /// See [`Module::find_function`] for possible error cases.
     ///
+    /// If several modules contain a function by the given name, the one of the most recently
+    /// loaded module is returned. With the `std` feature enabled, lookups are cached by name until
+    /// the next module gets loaded.
+    ///
     /// [`Module::find_function`]: ../module/struct.Module.html#method.find_function
     pub fn find_function<ARGS, RET>(self: &Rc<Self>, name: &str) -> Result<Function<ARGS, RET>>
     where
         ARGS: crate::WasmArgs,
         RET: crate::WasmType,
     {
-        self.modules()
-            .find_map(|module| match module.find_function::<ARGS, RET>(name) {
-                res @ Ok(_) | res @ Err(Error::InvalidFunctionSignature) => Some(res),
-                _ => None,
-            })
-            .unwrap_or(Err(Error::FunctionNotFound))
+        self.find_raw_function(name)
+            .and_then(|func| Function::from_raw(self.clone(), func))
+    }
+
+    // looks up the function in the most recently loaded module that has one by the given name
+    fn find_raw_function(self: &Rc<Self>, name: &str) -> Result<NNM3Function> {
+        #[cfg(feature = "std")]
+        {
+            if let Some(&func) = self.function_cache.borrow().get(name) {
+                return Ok(func);
+            }
+        }
+        let func = self
+            .modules()
+            .find_map(|module| module.find_raw_function(name).ok())
+            .ok_or(Error::FunctionNotFound)?;
+        #[cfg(feature = "std")]
+        {
+            self.function_cache
+                .borrow_mut()
+                .insert(name.to_string(), func);
+        }
+        Ok(func)
+    }
+
+    /// Like [`Runtime::find_function`], but returns `Ok(None)` if no module contains a function by
+    /// the given name instead of an error, which allows probing for optional functions.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the function has been found but its signature did not
+    /// match, or on memory allocation failure.
+    ///
+    /// [`Runtime::find_function`]: struct.Runtime.html#method.find_function
+    pub fn try_find_function<ARGS, RET>(
+        self: &Rc<Self>,
+        name: &str,
+    ) -> Result<Option<Function<ARGS, RET>>>
+    where
+        ARGS: crate::WasmArgs,
+        RET: crate::WasmType,
+    {
+        match self.find_function(name) {
+            Ok(func) => Ok(Some(func)),
+            Err(Error::FunctionNotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
     }
 
     /// Searches for a module with the given name in the runtime's loaded modules.
@@ -105,6 +329,9 @@ impl Runtime {
     }
 
     /// Returns an iterator over the runtime's loaded modules.
+    ///
+    /// The modules are visited in reverse load order, that is the most recently loaded module
+    /// comes first. This order is stable for a given sequence of loads.
     pub fn modules<'rt>(self: &'rt Rc<Self>) -> impl Iterator<Item = Module> + 'rt {
         // pointer could get invalidated if modules can become unloaded
         // pushing new modules into the runtime while this iterator exists is fine as its backed by a linked list meaning it wont get invalidated.
@@ -115,12 +342,182 @@ impl Runtime {
         })
     }
 
+    /// Returns the runtime's loaded modules sorted by name.
+    ///
+    /// Modules sharing a name keep the order of [`Runtime::modules`], most recently loaded first.
+    ///
+    /// [`Runtime::modules`]: struct.Runtime.html#method.modules
+    pub fn modules_sorted(self: &Rc<Self>) -> Vec<Module> {
+        let mut modules: Vec<_> = self.modules().collect();
+        modules.sort_by(|a, b| a.name().cmp(b.name()));
+        modules
+    }
+
+    /// Returns a report about the last function call of this runtime that failed.
+    ///
+    /// The report is kept until the next failing call or until [`Runtime::clear_last_trap`]
+    /// is called, successful calls do not clear it.
+    ///
+    /// [`Runtime::clear_last_trap`]: struct.Runtime.html#method.clear_last_trap
+    pub fn last_trap(&self) -> Option<TrapReport> {
+        self.last_trap.borrow().clone()
+    }
+
+    /// Clears the currently stored trap report, returning it.
+    pub fn clear_last_trap(&self) -> Option<TrapReport> {
+        self.last_trap.borrow_mut().take()
+    }
+
+    /// Sets a host-side label for this runtime which is included in its `Debug` output and in
+    /// trap reports, replacing any previously set name.
+    pub fn set_name(&self, name: &str) {
+        *self.name.borrow_mut() = Some(name.into());
+    }
+
+    /// The name of this runtime, if one has been set with [`Runtime::set_name`].
+    ///
+    /// The name is returned as a shared string, as it may be replaced at any time while the
+    /// returned name is still in use.
+    ///
+    /// [`Runtime::set_name`]: struct.Runtime.html#method.set_name
+    pub fn name(&self) -> Option<Rc<str>> {
+        self.name.borrow().clone()
+    }
+
+    /// Returns the number of bytes of operation stream wasm3 has compiled into this runtime's code
+    /// pages so far, including the trampolines of linked host functions.
+    ///
+    /// wasm3 shares code pages between all modules of a runtime, so the compiled code cannot be
+    /// attributed to individual modules. As functions are compiled lazily, this grows as more of
+    /// them are looked up or called.
+    pub fn compiled_code_size(&self) -> usize {
+        let mut lines = 0;
+        unsafe {
+            for &list in &[self.raw.as_ref().pagesOpen, self.raw.as_ref().pagesFull] {
+                let mut page = list;
+                while let Some(p) = page.as_ref() {
+                    lines += p.info.lineIndex as usize;
+                    page = p.info.next;
+                }
+            }
+        }
+        lines * mem::size_of::<ffi::code_t>()
+    }
+
+    /// Installs a handler that is invoked with the trap report whenever a function call of this
+    /// runtime fails, replacing any previously installed handler.
+    ///
+    /// The handler runs just before the failed call returns its `Err`, after wasm3 has already
+    /// unwound the call. It sees the runtime's memory as the trapping code left it, but the stack
+    /// frames of the trapping function are gone.
+    pub fn set_trap_handler<F>(&self, handler: F)
+    where
+        F: FnMut(&TrapReport) + 'static,
+    {
+        *self.trap_handler.borrow_mut() = Some(TrapHandler(Box::new(handler)));
+    }
+
+    /// Designates the `len` bytes at `offset` of this runtime's memory as scratch space that the
+    /// host may overwrite before calls, see [`Function::call_with_bytes`].
+    ///
+    /// All modules of a runtime share its memory, so the region applies to all of them. The guest
+    /// has to reserve the region, for example by exporting a static buffer, and must not keep
+    /// data in it across calls.
+    ///
+    /// [`Function::call_with_bytes`]: struct.Function.html#method.call_with_bytes
+    pub fn set_scratch_region(&self, offset: u32, len: u32) {
+        self.scratch_region.set(Some((offset, len)));
+    }
+
+    /// The scratch region set with [`Runtime::set_scratch_region`] as offset and length.
+    ///
+    /// [`Runtime::set_scratch_region`]: struct.Runtime.html#method.set_scratch_region
+    pub fn scratch_region(&self) -> Option<(u32, u32)> {
+        self.scratch_region.get()
+    }
+
+    /// Guards this runtime's memory against changes, allowing results to borrow from it.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the memory is already guarded.
+    pub fn guard_memory(self: &Rc<Self>) -> Result<MemoryGuard> {
+        if self.memory_guarded.replace(true) {
+            return Err(Error::MemoryGuarded);
+        }
+        Ok(MemoryGuard::new(self.clone()))
+    }
+
+    pub(crate) fn memory_guarded(&self) -> bool {
+        self.memory_guarded.get()
+    }
+
+    pub(crate) fn set_memory_guarded(&self, guarded: bool) {
+        self.memory_guarded.set(guarded);
+    }
+
+    /// Returns the current size and the limits of this runtime's memory.
+    pub fn memory_info(&self) -> MemoryInfo {
+        let memory = unsafe { &self.raw.as_ref().memory };
+        MemoryInfo {
+            size: if memory.mallocated.is_null() {
+                0
+            } else {
+                unsafe { (*memory.mallocated).length }
+            },
+            num_pages: memory.numPages,
+            max_pages: memory.maxPages,
+        }
+    }
+
+    /// Summarizes the layout of this runtime's memory from the data segments and the exported
+    /// `__data_end` and `__heap_base` globals of its loaded modules.
+    ///
+    /// Data segments whose offsets cannot be determined are left out.
+    pub fn memory_map(self: &Rc<Self>) -> MemoryMap {
+        let mut map = MemoryMap {
+            size: unsafe { (&*self.memory()).len() },
+            data_segments: Vec::new(),
+            data_end: None,
+            heap_base: None,
+        };
+        let global_u32 = |module: &Module, name| match module.find_global(name).map(|g| g.get()) {
+            Ok(Value::I32(val)) => Some(val as u32),
+            _ => None,
+        };
+        for &(raw, ref data) in unsafe { &*self.module_data.get() } {
+            let module = Module::from_raw(self.clone(), raw);
+            for (offset, len) in parse::data_segments(data) {
+                let start = match offset {
+                    Some(Offset::Const(offset)) => offset as u32,
+                    Some(Offset::Global(index)) => match module.global_at(index).map(|g| g.get()) {
+                        Some(Value::I32(offset)) => offset as u32,
+                        _ => continue,
+                    },
+                    None => continue,
+                };
+                map.data_segments.push(start..start.saturating_add(len));
+            }
+            map.data_end = map.data_end.or_else(|| global_u32(&module, "__data_end"));
+            map.heap_base = map.heap_base.or_else(|| global_u32(&module, "__heap_base"));
+        }
+        map.data_segments
+            .sort_by_key(|range| (range.start, range.end));
+        map
+    }
+
     /// Resizes the number of allocatable pages to num_pages.
     ///
     /// # Errors
     ///
-    /// This function will error out if it failed to resize memory allocation.
+    /// This function will error out if it failed to resize memory allocation or if the memory is
+    /// currently guarded by a [`MemoryGuard`].
+    ///
+    /// [`MemoryGuard`]: struct.MemoryGuard.html
     pub fn resize_memory(&self, num_pages: u32) -> Result<()> {
+        if self.memory_guarded() {
+            return Err(Error::MemoryGuarded);
+        }
         Error::from_ffi_res(unsafe { ffi::ResizeMemory(self.raw.as_ptr(), num_pages) })
     }
 
@@ -130,7 +527,8 @@ impl Runtime {
     ///
     /// The returned pointer may get invalidated when wasm function objects are called due to reallocations.
     pub unsafe fn memory(&self) -> *const [u8] {
-        let len = (*self.mallocated()).length as usize;
+        // no memory has been allocated until a module that does not import its memory is loaded
+        let len = self.memory_info().size;
         let data = if len == 0 {
             ptr::NonNull::dangling().as_ptr()
         } else {
@@ -145,7 +543,8 @@ impl Runtime {
     ///
     /// The returned pointer may get invalidated when wasm function objects are called due to reallocations.
     pub unsafe fn memory_mut(&self) -> *mut [u8] {
-        let len = (*self.mallocated()).length as usize;
+        // no memory has been allocated until a module that does not import its memory is loaded
+        let len = self.memory_info().size;
         let data = if len == 0 {
             ptr::NonNull::dangling().as_ptr()
         } else {
@@ -154,6 +553,144 @@ impl Runtime {
         ptr::slice_from_raw_parts_mut(data, len)
     }
 
+    /// Copies `len` bytes from this runtime's memory at `src_offset` into the memory of `dst`
+    /// at `dst_offset`, without going through an intermediate buffer.
+    ///
+    /// `dst` may be this runtime, in which case the regions are allowed to overlap.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if either region is out of the bounds of its runtime's memory or
+    /// if the memory of `dst` is currently guarded by a [`MemoryGuard`].
+    ///
+    /// [`MemoryGuard`]: struct.MemoryGuard.html
+    pub fn copy_memory_to(
+        &self,
+        src_offset: usize,
+        dst: &Runtime,
+        dst_offset: usize,
+        len: usize,
+    ) -> Result<()> {
+        if dst.memory_guarded() {
+            return Err(Error::MemoryGuarded);
+        }
+        unsafe {
+            let src = self.memory();
+            let dst = dst.memory_mut();
+            match (src_offset.checked_add(len), dst_offset.checked_add(len)) {
+                (Some(src_end), Some(dst_end)) if src_end <= src.len() && dst_end <= dst.len() => {
+                    ptr::copy(
+                        src.cast::<u8>().add(src_offset),
+                        dst.cast::<u8>().add(dst_offset),
+                        len,
+                    );
+                    Ok(())
+                }
+                _ => Err(Error::MemoryOutOfBounds),
+            }
+        }
+    }
+
+    /// Returns this runtime's memory, borrowing it if the memory is guarded and copying it
+    /// otherwise.
+    ///
+    /// A [`MemoryGuard`] keeps calls and resizes from invalidating the memory, so with a guard
+    /// the memory can be borrowed for as long as the guard lives. Borrowing uses up the guard,
+    /// just like [`Function::call_returning_str_borrowed`] does, so a guard that has already
+    /// been used yields a copy instead. Without a guard, the memory is copied, which is always
+    /// safe but costs an allocation the size of the memory.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the guard belongs to a different runtime.
+    ///
+    /// [`MemoryGuard`]: struct.MemoryGuard.html
+    /// [`Function::call_returning_str_borrowed`]: struct.Function.html#method.call_returning_str_borrowed
+    pub fn memory_cow<'g>(&self, guard: Option<&'g MemoryGuard>) -> Cow<'g, [u8]> {
+        match guard {
+            Some(guard) => {
+                assert!(
+                    ptr::eq(&**guard.runtime(), self),
+                    "memory guard belongs to a different runtime"
+                );
+                if guard.take_use() {
+                    Cow::Owned(unsafe { &*self.memory() }.to_vec())
+                } else {
+                    Cow::Borrowed(unsafe { &*guard.runtime().memory() })
+                }
+            }
+            None => Cow::Owned(unsafe { &*self.memory() }.to_vec()),
+        }
+    }
+
+    /// Takes a full copy of this runtime's memory.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            num_pages: self.memory_info().num_pages,
+            memory: unsafe { &*self.memory() }.into(),
+        }
+    }
+
+    /// Restores this runtime's memory, including its size, to the state of the given snapshot.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if resizing the memory fails.
+    pub fn restore_snapshot(&self, snapshot: &Snapshot) -> Result<()> {
+        self.resize_memory(snapshot.num_pages)?;
+        let memory = unsafe { &mut *self.memory_mut() };
+        let len = memory.len().min(snapshot.memory.len());
+        memory[..len].copy_from_slice(&snapshot.memory[..len]);
+        Ok(())
+    }
+
+    /// Records the parts of this runtime's memory that changed since `base` was taken.
+    ///
+    /// Memory is compared in chunks of [`MemoryDiff::CHUNK_SIZE`] bytes, and every chunk that
+    /// differs in at least one byte is copied into the diff as a whole, so the diff only takes
+    /// up as much space as the touched chunks. Computing it compares the entire memory against
+    /// the snapshot though, so its cost grows with the size of the memory rather than with the
+    /// amount of changes.
+    ///
+    /// [`MemoryDiff::CHUNK_SIZE`]: struct.MemoryDiff.html#associatedconstant.CHUNK_SIZE
+    pub fn snapshot_diff(&self, base: &Snapshot) -> MemoryDiff {
+        let memory = unsafe { &*self.memory() };
+        let chunks = memory
+            .chunks(MemoryDiff::CHUNK_SIZE)
+            .zip(0..)
+            .filter(|&(chunk, index)| {
+                let start = index as usize * MemoryDiff::CHUNK_SIZE;
+                base.memory.get(start..start + chunk.len()) != Some(chunk)
+            })
+            .map(|(chunk, index)| (index, chunk.into()))
+            .collect();
+        MemoryDiff {
+            num_pages: self.memory_info().num_pages,
+            len: memory.len(),
+            chunks,
+        }
+    }
+
+    /// Restores this runtime's memory to the state it was in when `diff` was recorded against
+    /// `base`.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if resizing the memory fails or if the memory ends up with a
+    /// different size than the one the diff was recorded at.
+    pub fn restore_diff(&self, base: &Snapshot, diff: &MemoryDiff) -> Result<()> {
+        self.restore_snapshot(base)?;
+        self.resize_memory(diff.num_pages)?;
+        let memory = unsafe { &mut *self.memory_mut() };
+        if memory.len() != diff.len {
+            return Err(Error::MemoryOutOfBounds);
+        }
+        for (offset, chunk) in diff.chunks() {
+            memory[offset..offset + chunk.len()].copy_from_slice(chunk);
+        }
+        Ok(())
+    }
+
     /// Returns the stack of this runtime.
     pub fn stack(&self) -> *const [ffi::m3slot_t] {
         unsafe {
@@ -184,6 +721,27 @@ impl Runtime {
         unsafe { (*self.closure_store.get()).push(closure) };
     }
 
+    /// Returns the bytes the given loaded module was parsed from.
+    pub(crate) fn module_data(&self, module: ffi::IM3Module) -> Option<&[u8]> {
+        unsafe { &*self.module_data.get() }
+            .iter()
+            .find(|(raw, _)| *raw == module)
+            .map(|(_, data)| &**data)
+    }
+
+    pub(crate) fn set_last_trap(&self, report: TrapReport) {
+        // take the handler out so that it may call into this runtime itself
+        let handler = self.trap_handler.borrow_mut().take();
+        if let Some(mut handler) = handler {
+            (handler.0)(&report);
+            let mut slot = self.trap_handler.borrow_mut();
+            if slot.is_none() {
+                *slot = Some(handler);
+            }
+        }
+        *self.last_trap.borrow_mut() = Some(report);
+    }
+
     pub(crate) fn as_ptr(&self) -> ffi::IM3Runtime {
         self.raw.as_ptr()
     }
@@ -200,3 +758,17 @@ fn create_and_drop_rt() {
     let env = Environment::new().expect("env alloc failure");
     assert!(Runtime::new(&env, 1024 * 64).is_ok());
 }
+
+#[test]
+fn rt_name() {
+    let env = Environment::new().expect("env alloc failure");
+    let rt = Runtime::new(&env, 1024 * 64).expect("runtime alloc failure");
+    assert_eq!(rt.name(), None);
+    rt.set_name("tenant-1");
+    assert_eq!(rt.name().as_deref(), Some("tenant-1"));
+    let old = rt.name();
+    rt.set_name("tenant-2");
+    assert_eq!(old.as_deref(), Some("tenant-1"));
+    assert_eq!(rt.name().as_deref(), Some("tenant-2"));
+    assert!(alloc::format!("{:?}", rt).contains("tenant-2"));
+}
@@ -0,0 +1,48 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// A full copy of a runtime's memory, see [`Runtime::snapshot`].
+///
+/// [`Runtime::snapshot`]: struct.Runtime.html#method.snapshot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub(crate) num_pages: u32,
+    pub(crate) memory: Box<[u8]>,
+}
+
+impl Snapshot {
+    /// The copied memory.
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+}
+
+/// The chunks of a runtime's memory that changed relative to a [`Snapshot`], see
+/// [`Runtime::snapshot_diff`].
+///
+/// [`Snapshot`]: struct.Snapshot.html
+/// [`Runtime::snapshot_diff`]: struct.Runtime.html#method.snapshot_diff
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryDiff {
+    pub(crate) num_pages: u32,
+    pub(crate) len: usize,
+    // index of the chunk and its new contents, the last chunk may be shorter
+    pub(crate) chunks: Vec<(u32, Box<[u8]>)>,
+}
+
+impl MemoryDiff {
+    /// The granularity at which changes are recorded, in bytes.
+    pub const CHUNK_SIZE: usize = 4096;
+
+    /// Returns an iterator over the offsets and new contents of all changed chunks.
+    pub fn chunks(&self) -> impl Iterator<Item = (usize, &[u8])> {
+        self.chunks
+            .iter()
+            .map(|(index, chunk)| (*index as usize * Self::CHUNK_SIZE, &**chunk))
+    }
+
+    /// Whether the memory was left unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
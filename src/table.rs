@@ -0,0 +1,28 @@
+/// The function table of a loaded module.
+///
+/// wasm3 does not support the reference types proposal, so guests cannot grow their table with
+/// `table.grow` and its size is fixed once the module has been loaded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Table {
+    capacity: u32,
+    max: Option<u32>,
+}
+
+impl Table {
+    pub(crate) fn new(capacity: u32, max: Option<u32>) -> Self {
+        Table { capacity, max }
+    }
+
+    /// The number of slots of this table.
+    ///
+    /// wasm3 only allocates as many slots as the module's element segments fill, so this may be
+    /// smaller than the minimum size the module declares.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// The maximum size the module declares for this table, if any.
+    pub fn max(&self) -> Option<u32> {
+        self.max
+    }
+}
@@ -74,6 +74,38 @@ pub trait WasmType: Sized {
 /// Tait implemented by types that can be passed to wasm.
 pub trait WasmArg: WasmType {}
 
+/// Implemented by the `WasmType` derive for newtypes around a [`WasmType`], which then get
+/// `WasmType` and `WasmArg` through the blanket impls below.
+///
+/// The values only ever cross the stack as `Inner`, so a wrong impl of this trait can not break
+/// the stack handling.
+#[doc(hidden)]
+pub trait Newtype: Sized {
+    type Inner: WasmType;
+    fn from_inner(inner: Self::Inner) -> Self;
+    fn into_inner(self) -> Self::Inner;
+}
+
+impl<T: Newtype> WasmArg for T where T::Inner: WasmArg {}
+impl<T: Newtype> WasmType for T {
+    #[doc(hidden)]
+    const TYPE_INDEX: u8 = T::Inner::TYPE_INDEX;
+    #[doc(hidden)]
+    const SIZE_IN_SLOT_COUNT: usize = T::Inner::SIZE_IN_SLOT_COUNT;
+    #[doc(hidden)]
+    unsafe fn pop_from_stack(stack: *mut ffi::m3slot_t) -> Self {
+        T::from_inner(T::Inner::pop_from_stack(stack))
+    }
+    #[doc(hidden)]
+    unsafe fn push_on_stack(self, stack: *mut ffi::m3slot_t) {
+        self.into_inner().push_on_stack(stack);
+    }
+    #[doc(hidden)]
+    fn sealed_() -> private::Seal {
+        private::Seal
+    }
+}
+
 /// Helper tait implemented by tuples to emulate "variadic generics".
 pub trait WasmArgs {
     #[doc(hidden)]
@@ -87,6 +119,160 @@ pub trait WasmArgs {
     fn sealed_() -> private::Seal;
 }
 
+/// The type of a wasm value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    /// A 32 bit integer.
+    I32,
+    /// A 64 bit integer.
+    I64,
+    /// A 32 bit float.
+    F32,
+    /// A 64 bit float.
+    F64,
+}
+
+impl ValueType {
+    pub(crate) fn from_type_index(idx: u8) -> Option<Self> {
+        match idx as ffi::_bindgen_ty_1::Type {
+            ffi::_bindgen_ty_1::c_m3Type_i32 => Some(ValueType::I32),
+            ffi::_bindgen_ty_1::c_m3Type_i64 => Some(ValueType::I64),
+            ffi::_bindgen_ty_1::c_m3Type_f32 => Some(ValueType::F32),
+            ffi::_bindgen_ty_1::c_m3Type_f64 => Some(ValueType::F64),
+            _ => None,
+        }
+    }
+
+    // the size of a value of this type in linear memory
+    pub(crate) fn byte_size(self) -> u32 {
+        match self {
+            ValueType::I32 | ValueType::F32 => 4,
+            ValueType::I64 | ValueType::F64 => 8,
+        }
+    }
+
+    pub(crate) fn type_index(self) -> u8 {
+        match self {
+            ValueType::I32 => i32::TYPE_INDEX,
+            ValueType::I64 => i64::TYPE_INDEX,
+            ValueType::F32 => f32::TYPE_INDEX,
+            ValueType::F64 => f64::TYPE_INDEX,
+        }
+    }
+}
+
+impl core::fmt::Display for ValueType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ValueType::I32 => "i32",
+            ValueType::I64 => "i64",
+            ValueType::F32 => "f32",
+            ValueType::F64 => "f64",
+        })
+    }
+}
+
+/// The argument and return types of a wasm function.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Signature {
+    /// The types of the arguments in order.
+    pub args: alloc::vec::Vec<ValueType>,
+    /// The return type, or `None` if the function returns nothing.
+    pub ret: Option<ValueType>,
+}
+
+impl core::fmt::Display for Signature {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("(")?;
+        for (i, arg) in self.args.iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            core::fmt::Display::fmt(arg, f)?;
+        }
+        f.write_str(")")?;
+        match self.ret {
+            Some(ret) => write!(f, " -> {}", ret),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Signature {
+    pub(crate) fn from_func_type(func_type: &ffi::M3FuncType) -> Self {
+        // argTypes is actually dynamically sized.
+        let args = unsafe {
+            core::slice::from_raw_parts(func_type.argTypes.as_ptr(), func_type.numArgs as usize)
+        };
+        Signature {
+            args: args
+                .iter()
+                .filter_map(|&ty| ValueType::from_type_index(ty))
+                .collect(),
+            ret: ValueType::from_type_index(func_type.returnType),
+        }
+    }
+}
+
+/// A wasm value whose type is only known at runtime.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Value {
+    /// A 32 bit integer.
+    I32(i32),
+    /// A 64 bit integer.
+    I64(i64),
+    /// A 32 bit float.
+    F32(f32),
+    /// A 64 bit float.
+    F64(f64),
+}
+
+impl Value {
+    // all value types occupy the same amount of slots
+    pub(crate) const SIZE_IN_SLOT_COUNT: usize = SIZE_IN_SLOT_COUNT;
+
+    /// The type of this value.
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Value::I32(_) => ValueType::I32,
+            Value::I64(_) => ValueType::I64,
+            Value::F32(_) => ValueType::F32,
+            Value::F64(_) => ValueType::F64,
+        }
+    }
+
+    // bytes has to hold exactly `ty.byte_size()` bytes
+    pub(crate) fn from_le_bytes(ty: ValueType, bytes: &[u8]) -> Self {
+        let mut buf = [0; 8];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        let low = [buf[0], buf[1], buf[2], buf[3]];
+        match ty {
+            ValueType::I32 => Value::I32(i32::from_le_bytes(low)),
+            ValueType::I64 => Value::I64(i64::from_le_bytes(buf)),
+            ValueType::F32 => Value::F32(f32::from_bits(u32::from_le_bytes(low))),
+            ValueType::F64 => Value::F64(f64::from_bits(u64::from_le_bytes(buf))),
+        }
+    }
+
+    pub(crate) unsafe fn pop_from_stack(ty: ValueType, stack: *mut ffi::m3slot_t) -> Self {
+        match ty {
+            ValueType::I32 => Value::I32(WasmType::pop_from_stack(stack)),
+            ValueType::I64 => Value::I64(WasmType::pop_from_stack(stack)),
+            ValueType::F32 => Value::F32(WasmType::pop_from_stack(stack)),
+            ValueType::F64 => Value::F64(WasmType::pop_from_stack(stack)),
+        }
+    }
+
+    pub(crate) unsafe fn push_on_stack(self, stack: *mut ffi::m3slot_t) {
+        match self {
+            Value::I32(val) => WasmType::push_on_stack(val, stack),
+            Value::I64(val) => WasmType::push_on_stack(val, stack),
+            Value::F32(val) => WasmType::push_on_stack(val, stack),
+            Value::F64(val) => WasmType::push_on_stack(val, stack),
+        }
+    }
+}
+
 impl WasmArg for i32 {}
 impl WasmType for i32 {
     #[doc(hidden)]
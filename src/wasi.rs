@@ -0,0 +1,75 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use core::ptr;
+
+use crate::environment::Environment;
+use crate::error::{Error, Result, Trap};
+
+/// The configuration of a WASI command run by [`run_wasi`].
+///
+/// [`run_wasi`]: fn.run_wasi.html
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WasiConfig {
+    /// The command line arguments passed to the module, starting with the program name.
+    ///
+    /// Arguments are cut off at their first nul byte.
+    pub args: Vec<String>,
+    /// The stack size of the runtime in bytes.
+    pub stack_size: u32,
+}
+
+impl Default for WasiConfig {
+    fn default() -> Self {
+        WasiConfig {
+            args: Vec::new(),
+            stack_size: 1024 * 60,
+        }
+    }
+}
+
+/// Runs a WASI command module in a fresh environment and runtime, returning its exit code.
+///
+/// This parses and loads the module, links WASI to it and calls its `_start` function. A module
+/// that returns from `_start` without calling `proc_exit` exits with code 0.
+///
+/// # Errors
+///
+/// This function will error if the module fails to parse, load or link, if it does not export a
+/// `_start` function taking no arguments or if it traps.
+pub fn run_wasi(bytes: &[u8], config: WasiConfig) -> Result<u32> {
+    let env = Environment::new()?;
+    let rt = env.create_runtime(config.stack_size)?;
+    let mut module = rt.parse_and_load_module(bytes)?;
+    module.link_wasi()?;
+    let start = module.find_function::<(), ()>("_start")?;
+
+    let args: Vec<Vec<u8>> = config
+        .args
+        .iter()
+        .map(|arg| {
+            let mut arg = Vec::from(arg.as_bytes());
+            arg.push(0);
+            arg
+        })
+        .collect();
+    let argv: Vec<*const cty::c_char> = args.iter().map(|arg| arg.as_ptr().cast()).collect();
+    let raw = rt.as_ptr();
+    unsafe {
+        (*raw).argc = argv.len() as u32;
+        (*raw).argv = argv.as_ptr() as *mut _;
+    }
+    let res = start.call();
+    // the arguments are dropped at the end of this function
+    unsafe {
+        (*raw).argc = 0;
+        (*raw).argv = ptr::null_mut();
+    }
+    match res {
+        Ok(()) => Ok(0),
+        Err(Error::Wasm3(error)) if error.is_trap(Trap::Exit) => {
+            Ok(unsafe { (*raw).exit_code } as u32)
+        }
+        Err(error) => Err(error),
+    }
+}
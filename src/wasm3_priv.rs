@@ -18,6 +18,7 @@ extern "C" {
     ) -> m3ret_t;
     pub fn EmitWord_impl(i_page: IM3CodePage, i_word: *mut cty::c_void);
     pub fn Compile_Function(io_function: IM3Function) -> M3Result;
+    pub fn InitGlobals(io_module: IM3Module) -> M3Result;
     pub fn AcquireCodePageWithCapacity(io_runtime: IM3Runtime, i_slotCount: u32) -> IM3CodePage;
     pub fn ReleaseCodePage(io_runtime: IM3Runtime, i_codePage: IM3CodePage);
     pub fn GetPagePC(i_page: IM3CodePage) -> pc_t;
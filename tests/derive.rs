@@ -0,0 +1,42 @@
+#![cfg(feature = "derive")]
+
+use wasm3::Environment;
+use wasm3::WasmType;
+
+#[derive(WasmType, Debug, PartialEq)]
+#[repr(transparent)]
+struct GuestPtr(u32);
+
+#[derive(WasmType, Debug, PartialEq)]
+#[repr(transparent)]
+pub struct Wide {
+    value: u64,
+}
+
+#[test]
+fn test_derive_wasm_type() {
+    let rt = Environment::new()
+        .expect("Unable to create environment")
+        .create_runtime(1024 * 60)
+        .expect("Unable to create runtime");
+    let module = rt
+        .parse_and_load_module(&include_bytes!("wasm_test_bins/wasm_test_bins.wasm")[..])
+        .expect("Unable to load module");
+    let func = module
+        .find_function::<(GuestPtr, GuestPtr), GuestPtr>("add_u32")
+        .expect("Unable to find function");
+    assert_eq!(func.call(GuestPtr(3), GuestPtr(6)), Ok(GuestPtr(9)));
+    let func = module
+        .find_function::<Wide, Wide>("invert")
+        .expect("Unable to find function");
+    assert_eq!(func.call(Wide { value: 0 }), Ok(Wide { value: !0 }));
+    assert!(module
+        .find_function::<(GuestPtr, GuestPtr), GuestPtr>("add_u64")
+        .is_err());
+}
+
+#[test]
+fn test_derive_wasm_type_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/derive/*.rs");
+}
@@ -0,0 +1,5 @@
+#[derive(wasm3::WasmType)]
+#[repr(transparent)]
+struct Empty {}
+
+fn main() {}
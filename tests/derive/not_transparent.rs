@@ -0,0 +1,4 @@
+#[derive(wasm3::WasmType)]
+struct GuestPtr(u32);
+
+fn main() {}
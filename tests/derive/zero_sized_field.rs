@@ -0,0 +1,10 @@
+use core::marker::PhantomData;
+
+#[derive(wasm3::WasmType)]
+#[repr(transparent)]
+struct Tagged {
+    value: u32,
+    tag: PhantomData<u8>,
+}
+
+fn main() {}
@@ -1,8 +1,213 @@
+use std::cell::Cell;
 use std::rc::Rc;
 
+use wasm3::error::Trap;
+use wasm3::CallContext;
+use wasm3::Clock;
 use wasm3::Environment;
 use wasm3::Module;
 use wasm3::Runtime;
+use wasm3::Signature;
+use wasm3::UnsupportedFeature;
+use wasm3::Value;
+use wasm3::ValueType;
+use wasm3::WasiModuleKind;
+use wasm3::{DisallowedImport, ExportKind};
+
+// (module (func (export "trap") unreachable))
+const TRAP_WASM: [u8; 35] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03, 0x02,
+    0x01, 0x00, 0x07, 0x08, 0x01, 0x04, 0x74, 0x72, 0x61, 0x70, 0x00, 0x00, 0x0a, 0x05, 0x01, 0x03,
+    0x00, 0x00, 0x0b,
+];
+
+// (module (func (export "_start")))
+const COMMAND_WASM: [u8; 36] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03, 0x02,
+    0x01, 0x00, 0x07, 0x0a, 0x01, 0x06, 0x5f, 0x73, 0x74, 0x61, 0x72, 0x74, 0x00, 0x00, 0x0a, 0x04,
+    0x01, 0x02, 0x00, 0x0b,
+];
+
+// (module (func (export "_initialize")))
+const REACTOR_WASM: [u8; 41] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03, 0x02,
+    0x01, 0x00, 0x07, 0x0f, 0x01, 0x0b, 0x5f, 0x69, 0x6e, 0x69, 0x74, 0x69, 0x61, 0x6c, 0x69, 0x7a,
+    0x65, 0x00, 0x00, 0x0a, 0x04, 0x01, 0x02, 0x00, 0x0b,
+];
+
+// (module
+//   (global $counter (mut i32) (i32.const 5))
+//   (func (export "bump") (result i32)
+//     (global.set $counter (i32.add (global.get $counter) (i32.const 1)))
+//     (global.get $counter)))
+const COUNTER_WASM: [u8; 52] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7f, 0x03,
+    0x02, 0x01, 0x00, 0x06, 0x06, 0x01, 0x7f, 0x01, 0x41, 0x05, 0x0b, 0x07, 0x08, 0x01, 0x04, 0x62,
+    0x75, 0x6d, 0x70, 0x00, 0x00, 0x0a, 0x0d, 0x01, 0x0b, 0x00, 0x23, 0x00, 0x41, 0x01, 0x6a, 0x24,
+    0x00, 0x23, 0x00, 0x0b,
+];
+
+// (module
+//   (memory 1)
+//   (data (i32.const 16) "hello\ff\fe")
+//   (func (export "identity") (param i64) (result i64) (local.get 0)))
+const IDENTITY_WASM: [u8; 62] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x06, 0x01, 0x60, 0x01, 0x7e, 0x01, 0x7e,
+    0x03, 0x02, 0x01, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x0c, 0x01, 0x08, 0x69, 0x64, 0x65,
+    0x6e, 0x74, 0x69, 0x74, 0x79, 0x00, 0x00, 0x0a, 0x06, 0x01, 0x04, 0x00, 0x20, 0x00, 0x0b, 0x0b,
+    0x0d, 0x01, 0x00, 0x41, 0x10, 0x0b, 0x07, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0xff, 0xfe,
+];
+
+// (module
+//   (memory 1)
+//   (func (export "first_plus_len") (param i32 i32) (result i32)
+//     (i32.add (i32.load8_u (local.get 0)) (local.get 1))))
+const FIRST_PLUS_LEN_WASM: [u8; 60] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x07, 0x01, 0x60, 0x02, 0x7f, 0x7f, 0x01,
+    0x7f, 0x03, 0x02, 0x01, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x07, 0x12, 0x01, 0x0e, 0x66, 0x69,
+    0x72, 0x73, 0x74, 0x5f, 0x70, 0x6c, 0x75, 0x73, 0x5f, 0x6c, 0x65, 0x6e, 0x00, 0x00, 0x0a, 0x0c,
+    0x01, 0x0a, 0x00, 0x20, 0x00, 0x2d, 0x00, 0x00, 0x20, 0x01, 0x6a, 0x0b,
+];
+
+// (module
+//   (import "env" "counter" (global $counter (mut i32)))
+//   (import "env" "scale" (global $scale f64))
+//   (func (export "counter") (result i32)
+//     (global.set $counter (i32.add (global.get $counter) (i32.const 1)))
+//     (global.get $counter))
+//   (func (export "scale") (result f64) (global.get $scale)))
+const IMPORTED_GLOBALS_WASM: [u8; 96] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x09, 0x02, 0x60, 0x00, 0x01, 0x7f, 0x60,
+    0x00, 0x01, 0x7c, 0x02, 0x1d, 0x02, 0x03, 0x65, 0x6e, 0x76, 0x07, 0x63, 0x6f, 0x75, 0x6e, 0x74,
+    0x65, 0x72, 0x03, 0x7f, 0x01, 0x03, 0x65, 0x6e, 0x76, 0x05, 0x73, 0x63, 0x61, 0x6c, 0x65, 0x03,
+    0x7c, 0x00, 0x03, 0x03, 0x02, 0x00, 0x01, 0x07, 0x13, 0x02, 0x07, 0x63, 0x6f, 0x75, 0x6e, 0x74,
+    0x65, 0x72, 0x00, 0x00, 0x05, 0x73, 0x63, 0x61, 0x6c, 0x65, 0x00, 0x01, 0x0a, 0x12, 0x02, 0x0b,
+    0x00, 0x23, 0x00, 0x41, 0x01, 0x6a, 0x24, 0x00, 0x23, 0x00, 0x0b, 0x04, 0x00, 0x23, 0x01, 0x0b,
+];
+
+// (module
+//   (memory 1)
+//   (global (export "__data_end") i32 (i32.const 512))
+//   (global (export "__heap_base") i32 (i32.const 1024))
+//   (data (i32.const 100) "abc"))
+const LAYOUT_WASM: [u8; 70] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x06, 0x0d, 0x02,
+    0x7f, 0x00, 0x41, 0x80, 0x04, 0x0b, 0x7f, 0x00, 0x41, 0x80, 0x08, 0x0b, 0x07, 0x1c, 0x02, 0x0a,
+    0x5f, 0x5f, 0x64, 0x61, 0x74, 0x61, 0x5f, 0x65, 0x6e, 0x64, 0x03, 0x00, 0x0b, 0x5f, 0x5f, 0x68,
+    0x65, 0x61, 0x70, 0x5f, 0x62, 0x61, 0x73, 0x65, 0x03, 0x01, 0x0b, 0x0a, 0x01, 0x00, 0x41, 0xe4,
+    0x00, 0x0b, 0x03, 0x61, 0x62, 0x63,
+];
+
+// (module
+//   (table 4 8 funcref)
+//   (func $f)
+//   (elem (i32.const 1) $f $f))
+const TABLE_WASM: [u8; 41] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03, 0x02,
+    0x01, 0x00, 0x04, 0x05, 0x01, 0x70, 0x01, 0x04, 0x08, 0x09, 0x08, 0x01, 0x00, 0x41, 0x01, 0x0b,
+    0x02, 0x00, 0x00, 0x0a, 0x04, 0x01, 0x02, 0x00, 0x0b,
+];
+
+// (module
+//   (import "wasi_snapshot_preview1" "args_sizes_get" (func $args_sizes_get (param i32 i32) (result i32)))
+//   (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+//   (memory (export "memory") 1)
+//   (func (export "_start")
+//     (drop (call $args_sizes_get (i32.const 0) (i32.const 4)))
+//     (call $proc_exit (i32.load (i32.const 0)))))
+#[cfg(feature = "wasi")]
+const EXIT_WITH_ARGC_WASM: [u8; 152] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0e, 0x03, 0x60, 0x02, 0x7f, 0x7f, 0x01,
+    0x7f, 0x60, 0x01, 0x7f, 0x00, 0x60, 0x00, 0x00, 0x02, 0x4c, 0x02, 0x16, 0x77, 0x61, 0x73, 0x69,
+    0x5f, 0x73, 0x6e, 0x61, 0x70, 0x73, 0x68, 0x6f, 0x74, 0x5f, 0x70, 0x72, 0x65, 0x76, 0x69, 0x65,
+    0x77, 0x31, 0x0e, 0x61, 0x72, 0x67, 0x73, 0x5f, 0x73, 0x69, 0x7a, 0x65, 0x73, 0x5f, 0x67, 0x65,
+    0x74, 0x00, 0x00, 0x16, 0x77, 0x61, 0x73, 0x69, 0x5f, 0x73, 0x6e, 0x61, 0x70, 0x73, 0x68, 0x6f,
+    0x74, 0x5f, 0x70, 0x72, 0x65, 0x76, 0x69, 0x65, 0x77, 0x31, 0x09, 0x70, 0x72, 0x6f, 0x63, 0x5f,
+    0x65, 0x78, 0x69, 0x74, 0x00, 0x01, 0x03, 0x02, 0x01, 0x02, 0x05, 0x03, 0x01, 0x00, 0x01, 0x07,
+    0x13, 0x02, 0x06, 0x5f, 0x73, 0x74, 0x61, 0x72, 0x74, 0x00, 0x02, 0x06, 0x6d, 0x65, 0x6d, 0x6f,
+    0x72, 0x79, 0x02, 0x00, 0x0a, 0x12, 0x01, 0x10, 0x00, 0x41, 0x00, 0x41, 0x04, 0x10, 0x00, 0x1a,
+    0x41, 0x00, 0x28, 0x02, 0x00, 0x10, 0x01, 0x0b,
+];
+
+// (module
+//   (func (export "identity_f32") (param f32) (result f32) (local.get 0))
+//   (func (export "identity_f64") (param f64) (result f64) (local.get 0)))
+const FLOAT_IDENTITY_WASM: [u8; 72] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0b, 0x02, 0x60, 0x01, 0x7d, 0x01, 0x7d,
+    0x60, 0x01, 0x7c, 0x01, 0x7c, 0x03, 0x03, 0x02, 0x00, 0x01, 0x07, 0x1f, 0x02, 0x0c, 0x69, 0x64,
+    0x65, 0x6e, 0x74, 0x69, 0x74, 0x79, 0x5f, 0x66, 0x33, 0x32, 0x00, 0x00, 0x0c, 0x69, 0x64, 0x65,
+    0x6e, 0x74, 0x69, 0x74, 0x79, 0x5f, 0x66, 0x36, 0x34, 0x00, 0x01, 0x0a, 0x0b, 0x02, 0x04, 0x00,
+    0x20, 0x00, 0x0b, 0x04, 0x00, 0x20, 0x00, 0x0b,
+];
+
+// (module
+//   (func $a (export "a"))
+//   (func (export "b"))
+//   (func (export "main") (call $a)))
+const CALLS_WASM: [u8; 52] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03, 0x04,
+    0x03, 0x00, 0x00, 0x00, 0x07, 0x10, 0x03, 0x01, 0x61, 0x00, 0x00, 0x01, 0x62, 0x00, 0x01, 0x04,
+    0x6d, 0x61, 0x69, 0x6e, 0x00, 0x02, 0x0a, 0x0c, 0x03, 0x02, 0x00, 0x0b, 0x02, 0x00, 0x0b, 0x04,
+    0x00, 0x10, 0x00, 0x0b,
+];
+
+// (module
+//   (import "env" "add" (func $add (param i32 i32) (result i32)))
+//   (import "env" "log" (func $log (param i32)))
+//   (func (export "run") (result i32)
+//     (call $log (i32.const 7))
+//     (call $add (i32.const 2) (i32.const 3))))
+const ADD_AND_LOG_WASM: [u8; 77] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x0f, 0x03, 0x60, 0x02, 0x7f, 0x7f, 0x01,
+    0x7f, 0x60, 0x01, 0x7f, 0x00, 0x60, 0x00, 0x01, 0x7f, 0x02, 0x15, 0x02, 0x03, 0x65, 0x6e, 0x76,
+    0x03, 0x61, 0x64, 0x64, 0x00, 0x00, 0x03, 0x65, 0x6e, 0x76, 0x03, 0x6c, 0x6f, 0x67, 0x00, 0x01,
+    0x03, 0x02, 0x01, 0x02, 0x07, 0x07, 0x01, 0x03, 0x72, 0x75, 0x6e, 0x00, 0x02, 0x0a, 0x0e, 0x01,
+    0x0c, 0x00, 0x41, 0x07, 0x10, 0x01, 0x41, 0x02, 0x41, 0x03, 0x10, 0x00, 0x0b,
+];
+
+// (module
+//   (memory 1)
+//   (func
+//     (memory.copy (i32.const 0) (i32.const 0) (i32.const 0))
+//     (return_call 0)))
+const POST_MVP_WASM: [u8; 41] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03, 0x02,
+    0x01, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01, 0x0a, 0x10, 0x01, 0x0e, 0x00, 0x41, 0x00, 0x41, 0x00,
+    0x41, 0x00, 0xfc, 0x0a, 0x00, 0x00, 0x12, 0x00, 0x0b,
+];
+
+// (module
+//   (func)
+//   (func (export "bad") (drop (i32.add))))
+const STACK_UNDERRUN_WASM: [u8; 39] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03, 0x03,
+    0x02, 0x00, 0x00, 0x07, 0x07, 0x01, 0x03, 0x62, 0x61, 0x64, 0x00, 0x01, 0x0a, 0x09, 0x02, 0x02,
+    0x00, 0x0b, 0x04, 0x00, 0x6a, 0x1a, 0x0b,
+];
+
+// (module
+//   (import "env" "init" (func $init))
+//   (func $start (call $init) (loop (br 0)))
+//   (func (export "answer") (result i32) (i32.const 42))
+//   (start $start))
+const START_LOOP_WASM: [u8; 70] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x60, 0x00, 0x00, 0x60, 0x00,
+    0x01, 0x7f, 0x02, 0x0c, 0x01, 0x03, 0x65, 0x6e, 0x76, 0x04, 0x69, 0x6e, 0x69, 0x74, 0x00, 0x00,
+    0x03, 0x03, 0x02, 0x00, 0x01, 0x07, 0x0a, 0x01, 0x06, 0x61, 0x6e, 0x73, 0x77, 0x65, 0x72, 0x00,
+    0x02, 0x08, 0x01, 0x01, 0x0a, 0x10, 0x02, 0x09, 0x00, 0x10, 0x00, 0x03, 0x40, 0x0c, 0x00, 0x0b,
+    0x0b, 0x04, 0x00, 0x41, 0x2a, 0x0b,
+];
+
+// (module
+//   (import "env" "missing" (func $missing))
+//   (func $inner (call $missing))
+//   (func (export "main") (call $inner)))
+const CALLS_MISSING_WASM: [u8; 59] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x02, 0x0f,
+    0x01, 0x03, 0x65, 0x6e, 0x76, 0x07, 0x6d, 0x69, 0x73, 0x73, 0x69, 0x6e, 0x67, 0x00, 0x00, 0x03,
+    0x03, 0x02, 0x00, 0x00, 0x07, 0x08, 0x01, 0x04, 0x6d, 0x61, 0x69, 0x6e, 0x00, 0x02, 0x0a, 0x0b,
+    0x02, 0x04, 0x00, 0x10, 0x00, 0x0b, 0x04, 0x00, 0x10, 0x01, 0x0b,
+];
 
 fn runtime() -> Rc<Runtime> {
     Environment::new()
@@ -86,6 +291,86 @@ fn test_no_args_no_ret_func() {
     assert_eq!(func.call(), Ok(()));
 }
 
+#[test]
+fn test_call_dynamic() {
+    let rt = runtime();
+    let module = module(&rt);
+    let func = module
+        .find_function_dynamic("add_u32")
+        .expect("Unable to find function");
+    assert_eq!(
+        func.call_dynamic(&[Value::I32(3), Value::I32(6)]),
+        Ok(Some(Value::I32(9)))
+    );
+    assert_eq!(
+        func.call_dynamic(&[Value::I32(3), Value::I64(6)]),
+        Err(wasm3::error::Error::InvalidFunctionSignature)
+    );
+    assert_eq!(
+        func.call_dynamic(&[Value::I32(3)]),
+        Err(wasm3::error::Error::InvalidFunctionSignature)
+    );
+    let func = module
+        .find_function_dynamic("no_return")
+        .expect("Unable to find function");
+    assert_eq!(func.call_dynamic(&[Value::I64(1)]), Ok(None));
+}
+
+#[test]
+fn test_compiled_code_size() {
+    let rt = runtime();
+    let module = module(&rt);
+    let before = rt.compiled_code_size();
+    module
+        .find_function::<(u64, u64), u64>("add_u64")
+        .expect("Unable to find function");
+    assert!(rt.compiled_code_size() > before);
+}
+
+#[test]
+fn test_try_find_function() {
+    let rt = runtime();
+    let module = module(&rt);
+    assert!(module
+        .try_find_function::<(u32, u32), u32>("add_u32")
+        .expect("Unable to probe function")
+        .is_some());
+    assert_eq!(module.try_find_function::<(), ()>("missing"), Ok(None));
+    assert_eq!(
+        module.try_find_function::<(), ()>("add_u32"),
+        Err(wasm3::error::Error::InvalidFunctionSignature)
+    );
+    assert_eq!(rt.try_find_function::<(), ()>("missing"), Ok(None));
+    assert!(rt
+        .try_find_function::<(), u64>("constant")
+        .expect("Unable to probe function")
+        .is_some());
+}
+
+#[test]
+fn test_find_function_shadowing() {
+    let rt = runtime();
+    let first = module(&rt);
+    let func = rt
+        .find_function::<(u32, u32), u32>("add_u32")
+        .expect("Unable to find function");
+    assert_eq!(
+        rt.find_function::<(u32, u32), u32>("add_u32").as_ref(),
+        Ok(&func)
+    );
+    assert_eq!(
+        first.find_function::<(u32, u32), u32>("add_u32").as_ref(),
+        Ok(&func)
+    );
+    assert!(rt.find_function::<(), ()>("add_u32").is_err());
+    let _second = module(&rt);
+    let shadowing = rt
+        .find_function::<(u32, u32), u32>("add_u32")
+        .expect("Unable to find function");
+    assert_ne!(shadowing, func);
+    assert_eq!(shadowing.call(1, 2), Ok(3));
+}
+
 #[test]
 fn test_resize_memory() {
     let rt = runtime();
@@ -100,3 +385,938 @@ fn test_resize_memory() {
     rt.resize_memory(10).expect("could not resize memory");
     assert_eq!(func.call(), Ok(10));
 }
+
+#[test]
+fn test_unallocated_memory() {
+    let rt = runtime();
+    let other = runtime();
+    assert_eq!(unsafe { (&*rt.memory()).len() }, 0);
+    assert_eq!(rt.memory_info().size, 0);
+    assert_eq!(rt.memory_map().size, 0);
+    assert_eq!(rt.memory_cow(None).len(), 0);
+    assert_eq!(rt.copy_memory_to(0, &other, 0, 0), Ok(()));
+    let snapshot = rt.snapshot();
+    assert!(snapshot.memory().is_empty());
+    let diff = rt.snapshot_diff(&snapshot);
+    assert!(diff.is_empty());
+    assert_eq!(rt.restore_diff(&snapshot, &diff), Ok(()));
+    let module = rt
+        .parse_and_load_module(&CALLS_WASM[..])
+        .expect("Unable to load module");
+    assert_eq!(rt.memory_info().size, 0);
+    let func = module
+        .find_function::<(), ()>("main")
+        .expect("Unable to find function");
+    assert_eq!(func.call_from_memory(0), Ok(()));
+}
+
+#[test]
+fn test_copy_memory_to() {
+    let src = runtime();
+    let _src_module = module(&src);
+    let dst = runtime();
+    let _dst_module = module(&dst);
+    src.resize_memory(1).expect("could not resize memory");
+    dst.resize_memory(1).expect("could not resize memory");
+    unsafe { (&mut *src.memory_mut())[16..20].copy_from_slice(b"wasm") };
+    assert_eq!(src.copy_memory_to(16, &dst, 32, 4), Ok(()));
+    assert_eq!(unsafe { &(&*dst.memory())[32..36] }, b"wasm");
+    assert_eq!(src.copy_memory_to(16, &src, 18, 4), Ok(()));
+    assert_eq!(unsafe { &(&*src.memory())[16..22] }, b"wawasm");
+    assert_eq!(
+        src.copy_memory_to(0, &dst, 65535, 2),
+        Err(wasm3::error::Error::MemoryOutOfBounds)
+    );
+    let guard = dst.guard_memory().expect("Unable to guard memory");
+    assert_eq!(
+        src.copy_memory_to(16, &dst, 40, 4),
+        Err(wasm3::error::Error::MemoryGuarded)
+    );
+    assert_eq!(unsafe { &(&*dst.memory())[40..44] }, [0; 4]);
+    drop(guard);
+    assert_eq!(dst.copy_memory_to(32, &src, 0, 4), Ok(()));
+}
+
+#[test]
+fn test_last_trap() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&TRAP_WASM[..])
+        .expect("Unable to load module");
+    let func = module
+        .find_function::<(), ()>("trap")
+        .expect("Unable to find function");
+    assert!(rt.last_trap().is_none());
+    assert!(func.call().is_err());
+    let report = rt.last_trap().expect("no trap was recorded");
+    assert_eq!(report.trap(), Some(Trap::Unreachable));
+    assert_eq!(report.function_name(), "trap");
+    assert_eq!(report.details(), None);
+    assert_eq!(report.runtime_name(), None);
+    assert_eq!(rt.clear_last_trap(), Some(report));
+    rt.set_name("trapper");
+    assert!(func.call().is_err());
+    let report = rt.last_trap().expect("no trap was recorded");
+    assert_eq!(report.runtime_name(), Some("trapper"));
+    assert!(report.to_string().ends_with("in runtime `trapper`"));
+    rt.clear_last_trap();
+    assert!(rt.last_trap().is_none());
+
+    // `inner` is only compiled once `main` calls it
+    let module = rt
+        .parse_and_load_module(&CALLS_MISSING_WASM[..])
+        .expect("Unable to load module");
+    let func = module
+        .find_function::<(), ()>("main")
+        .expect("Unable to find function");
+    assert!(func.call().is_err());
+    let report = rt.last_trap().expect("no trap was recorded");
+    assert_eq!(report.trap(), None);
+    assert_eq!(report.details(), Some("'env.missing'"));
+}
+
+#[test]
+fn test_call_timed() {
+    struct TickClock(Cell<u32>);
+    impl Clock for TickClock {
+        type Instant = u32;
+        type Duration = u32;
+        fn now(&self) -> u32 {
+            let now = self.0.get();
+            self.0.set(now + 3);
+            now
+        }
+        fn elapsed(&self, start: u32, end: u32) -> u32 {
+            end - start
+        }
+    }
+
+    let rt = runtime();
+    let module = module(&rt);
+    let func = module
+        .find_function::<(u64, u64), u64>("add_u64")
+        .expect("Unable to find function");
+    let clock = TickClock(Cell::new(0));
+    assert_eq!(func.call_timed((124, 612), &clock), Ok((736, 3)));
+}
+
+#[test]
+fn test_default_closure() {
+    let rt = runtime();
+    rt.link_default_closure("time", "millis", |_, ()| 5000u64);
+    let load = || {
+        rt.parse_and_load_module(
+            &include_bytes!("../examples/wasm/wasm_millis_to_seconds/wasm_millis_to_seconds.wasm")
+                [..],
+        )
+        .expect("Unable to load module")
+    };
+    let mut overridden = load();
+    overridden
+        .link_closure("time", "millis", |_, ()| 7000u64)
+        .expect("Unable to link closure");
+    let defaulted = load();
+    let func = defaulted
+        .find_function::<(), u64>("seconds")
+        .expect("Unable to find function");
+    assert_eq!(func.call(), Ok(5));
+    let func = overridden
+        .find_function::<(), u64>("seconds")
+        .expect("Unable to find function");
+    assert_eq!(func.call(), Ok(7));
+}
+
+#[test]
+fn test_default_closure_signature_mismatch() {
+    let rt = runtime();
+    rt.link_default_closure("env", "log", |_, (a, b): (i32, i32)| a + b);
+    let mut module = rt
+        .parse_and_load_module(&ADD_AND_LOG_WASM[..])
+        .expect("Unable to load module");
+    module
+        .link_closure("env", "log", |_, _val: i32| {})
+        .expect("Unable to link closure");
+    module
+        .link_closure("env", "add", |_, (a, b): (i32, i32)| a + b)
+        .expect("Unable to link closure");
+    let func = module
+        .find_function::<(), i32>("run")
+        .expect("Unable to find function");
+    assert_eq!(func.call(), Ok(5));
+}
+
+#[test]
+fn test_link_boxed_fn_mut() {
+    let rt = runtime();
+    let mut module = rt
+        .parse_and_load_module(
+            &include_bytes!("../examples/wasm/wasm_millis_to_seconds/wasm_millis_to_seconds.wasm")
+                [..],
+        )
+        .expect("Unable to load module");
+    let mut total = 0u64;
+    let millis: Box<dyn for<'cc> FnMut(CallContext<'cc>, ()) -> u64> = Box::new(move |_, ()| {
+        total += 1000;
+        total
+    });
+    module
+        .link_closure("time", "millis", millis)
+        .expect("Unable to link closure");
+    let func = module
+        .find_function::<(), u64>("seconds")
+        .expect("Unable to find function");
+    assert_eq!(func.call(), Ok(1));
+    assert_eq!(func.call(), Ok(2));
+    assert_eq!(func.call(), Ok(3));
+}
+
+#[test]
+fn test_wasi_module_kind() {
+    let rt = runtime();
+    let kind = |bytes: &[u8]| {
+        rt.parse_and_load_module(bytes)
+            .expect("Unable to load module")
+            .wasi_module_kind()
+    };
+    assert_eq!(kind(&COMMAND_WASM[..]), WasiModuleKind::Command);
+    assert_eq!(kind(&REACTOR_WASM[..]), WasiModuleKind::Reactor);
+    assert_eq!(kind(&TRAP_WASM[..]), WasiModuleKind::Unknown);
+}
+
+#[test]
+fn test_reset_globals() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&COUNTER_WASM[..])
+        .expect("Unable to load module");
+    let func = module
+        .find_function::<(), i32>("bump")
+        .expect("Unable to find function");
+    assert_eq!(func.call(), Ok(6));
+    assert_eq!(func.call(), Ok(7));
+    assert_eq!(module.reset_globals(), Ok(()));
+    assert_eq!(func.call(), Ok(6));
+}
+
+#[test]
+fn test_link_closure_raw() {
+    let rt = runtime();
+    let load = || {
+        rt.parse_and_load_module(
+            &include_bytes!("../examples/wasm/wasm_millis_to_seconds/wasm_millis_to_seconds.wasm")
+                [..],
+        )
+        .expect("Unable to load module")
+    };
+    let mut module = load();
+    module
+        .link_closure_raw::<(), u64, _>("time", "millis", |sp, _mem| {
+            unsafe { sp.cast::<u64>().write_unaligned(9000) };
+            Ok(())
+        })
+        .expect("Unable to link closure");
+    let func = module
+        .find_function::<(), u64>("seconds")
+        .expect("Unable to find function");
+    assert_eq!(func.call(), Ok(9));
+
+    let mut module = load();
+    module
+        .link_closure_raw::<(), u64, _>("time", "millis", |_, _| Err(Trap::Abort))
+        .expect("Unable to link closure");
+    let func = module
+        .find_function::<(), u64>("seconds")
+        .expect("Unable to find function");
+    assert!(func.call().is_err());
+    assert_eq!(rt.last_trap().and_then(|r| r.trap()), Some(Trap::Abort));
+}
+
+#[test]
+fn test_call_returning_bytes() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&IDENTITY_WASM[..])
+        .expect("Unable to load module");
+    let func = module
+        .find_function::<u64, u64>("identity")
+        .expect("Unable to find function");
+    let region = |ptr: u64, len: u64| ptr | len << 32;
+    assert_eq!(
+        func.call_returning_bytes(region(16, 7)),
+        Ok(b"hello\xff\xfe".to_vec())
+    );
+    assert_eq!(
+        func.call_returning_string(region(16, 5)),
+        Ok("hello".to_string())
+    );
+    assert_eq!(
+        func.call_returning_string(region(16, 6)),
+        Err(wasm3::error::Error::InvalidUtf8)
+    );
+    assert_eq!(
+        func.call_returning_bytes(region(65535, 2)),
+        Err(wasm3::error::Error::MemoryOutOfBounds)
+    );
+}
+
+#[test]
+fn test_iteration_order() {
+    let rt = runtime();
+    let _first = module(&rt);
+    let _second = rt
+        .parse_and_load_module(&TRAP_WASM[..])
+        .expect("Unable to load module");
+    let modules: Vec<_> = rt.modules().collect();
+    assert_eq!(modules.len(), 2);
+    assert_eq!(modules[0].function_names().collect::<Vec<_>>(), ["trap"]);
+    assert_eq!(rt.modules_sorted().len(), 2);
+
+    let names = modules[1].function_names_sorted();
+    assert!(names.contains(&"add_u32"));
+    assert!(names.windows(2).all(|w| w[0] <= w[1]));
+}
+
+#[test]
+fn test_verify_imports_allowed() {
+    let env = Environment::new().expect("Unable to create environment");
+    let module = env
+        .parse_module(
+            &include_bytes!("../examples/wasm/wasm_millis_to_seconds/wasm_millis_to_seconds.wasm")
+                [..],
+        )
+        .expect("Unable to parse module");
+    assert_eq!(module.verify_imports_allowed(&[("time", "millis")]), Ok(()));
+    assert_eq!(
+        module.verify_imports_allowed(&[("time", "micros")]),
+        Err(vec![DisallowedImport {
+            module_name: "time".to_string(),
+            name: "millis".to_string(),
+            kind: ExportKind::Function,
+        }])
+    );
+}
+
+#[test]
+fn test_trap_handler() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&TRAP_WASM[..])
+        .expect("Unable to load module");
+    let func = module
+        .find_function::<(), ()>("trap")
+        .expect("Unable to find function");
+    let traps = Rc::new(Cell::new(0));
+    let handler_traps = traps.clone();
+    rt.set_trap_handler(move |report| {
+        assert_eq!(report.trap(), Some(Trap::Unreachable));
+        handler_traps.set(handler_traps.get() + 1);
+    });
+    assert!(func.call().is_err());
+    assert!(func.call().is_err());
+    assert_eq!(traps.get(), 2);
+}
+
+#[test]
+fn test_call_with_bytes() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&FIRST_PLUS_LEN_WASM[..])
+        .expect("Unable to load module");
+    let func = module
+        .find_function::<(u32, u32), u32>("first_plus_len")
+        .expect("Unable to find function");
+    assert_eq!(
+        func.call_with_bytes(b"ab"),
+        Err(wasm3::error::Error::NoScratchRegion)
+    );
+    rt.set_scratch_region(1024, 4);
+    assert_eq!(func.call_with_bytes(&[40, 0]), Ok(42));
+    assert_eq!(unsafe { &(&*rt.memory())[1024..1026] }, [40, 0]);
+    assert_eq!(
+        func.call_with_bytes(b"hello"),
+        Err(wasm3::error::Error::MemoryOutOfBounds)
+    );
+    let guard = rt.guard_memory().expect("Unable to guard memory");
+    assert_eq!(
+        func.call_with_bytes(&[1, 2]),
+        Err(wasm3::error::Error::MemoryGuarded)
+    );
+    assert_eq!(unsafe { &(&*rt.memory())[1024..1026] }, [40, 0]);
+    drop(guard);
+}
+
+#[test]
+fn test_link_global() {
+    use wasm3::error::Error;
+
+    let rt = runtime();
+    let mut module = rt
+        .parse_and_load_module(&IMPORTED_GLOBALS_WASM[..])
+        .expect("Unable to load module");
+    assert_eq!(
+        module.link_global("env", "scale", Value::I32(2)),
+        Err(Error::InvalidGlobalType)
+    );
+    assert_eq!(
+        module.link_global("env", "missing", Value::I32(2)),
+        Err(Error::GlobalNotFound)
+    );
+    assert!(matches!(
+        module.link_global_mut("env", "scale", Value::F64(2.5)),
+        Err(Error::InvalidGlobalType)
+    ));
+    module
+        .link_global("env", "scale", Value::F64(2.5))
+        .expect("Unable to link global");
+    let counter = module
+        .link_global_mut("env", "counter", Value::I32(10))
+        .expect("Unable to link global");
+
+    let scale = module
+        .find_function::<(), f64>("scale")
+        .expect("Unable to find function");
+    assert_eq!(scale.call(), Ok(2.5));
+    let bump = module
+        .find_function::<(), i32>("counter")
+        .expect("Unable to find function");
+    assert_eq!(bump.call(), Ok(11));
+    assert_eq!(counter.get(), Value::I32(11));
+    counter.set(Value::I32(20)).expect("Unable to set global");
+    assert_eq!(bump.call(), Ok(21));
+}
+
+#[test]
+fn test_memory_map() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&LAYOUT_WASM[..])
+        .expect("Unable to load module");
+    assert_eq!(
+        module.find_global("__heap_base").map(|g| g.get()),
+        Ok(Value::I32(1024))
+    );
+    let map = rt.memory_map();
+    assert_eq!(map.size, 65536);
+    assert_eq!(map.data_segments, vec![100..103]);
+    assert_eq!(map.data_end, Some(512));
+    assert_eq!(map.heap_base, Some(1024));
+}
+
+#[test]
+fn test_call_returning_str_borrowed() {
+    let env = Environment::new().expect("Unable to create environment");
+    let rt = env
+        .create_runtime(1024 * 60)
+        .expect("Unable to create runtime");
+    let module = rt
+        .parse_and_load_module(&IDENTITY_WASM[..])
+        .expect("Unable to load module");
+    let func = module
+        .find_function::<u64, u64>("identity")
+        .expect("Unable to find function");
+    let region = |ptr: u64, len: u64| ptr | len << 32;
+    let guard = rt.guard_memory().expect("Unable to guard memory");
+    assert_eq!(
+        rt.guard_memory().err(),
+        Some(wasm3::error::Error::MemoryGuarded)
+    );
+    let hello = func.call_returning_str_borrowed(region(16, 5), &guard);
+    assert_eq!(hello, Ok("hello"));
+    assert_eq!(
+        func.call_returning_str_borrowed(region(16, 5), &guard),
+        Err(wasm3::error::Error::MemoryGuarded)
+    );
+    assert_eq!(
+        func.call(region(16, 5)),
+        Err(wasm3::error::Error::MemoryGuarded)
+    );
+    assert_eq!(rt.resize_memory(2), Err(wasm3::error::Error::MemoryGuarded));
+    assert_eq!(
+        rt.parse_and_load_module(&LAYOUT_WASM[..]).err(),
+        Some(wasm3::error::Error::MemoryGuarded)
+    );
+    let parsed = Module::parse(&env, &LAYOUT_WASM[..]).expect("Unable to parse module");
+    assert_eq!(
+        rt.load_module(parsed).err(),
+        Some(wasm3::error::Error::MemoryGuarded)
+    );
+    assert_eq!(hello, Ok("hello"));
+    drop(guard);
+    assert_eq!(func.call(region(16, 5)), Ok(region(16, 5)));
+}
+
+#[test]
+fn test_function_signatures() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&IMPORTED_GLOBALS_WASM[..])
+        .expect("Unable to load module");
+    let signatures: Vec<_> = module.function_signatures().collect();
+    assert_eq!(
+        signatures,
+        [
+            (
+                "counter".to_string(),
+                Signature {
+                    args: vec![],
+                    ret: Some(ValueType::I32)
+                }
+            ),
+            (
+                "scale".to_string(),
+                Signature {
+                    args: vec![],
+                    ret: Some(ValueType::F64)
+                }
+            ),
+        ]
+    );
+    let module = rt
+        .parse_and_load_module(&FIRST_PLUS_LEN_WASM[..])
+        .expect("Unable to load module");
+    let (name, signature) = module.function_signatures().next().unwrap();
+    assert_eq!(name, "first_plus_len");
+    assert_eq!(signature.args, [ValueType::I32, ValueType::I32]);
+}
+
+#[test]
+fn test_store_module_without_runtime() {
+    struct Plugin {
+        module: Module,
+    }
+    let plugin = {
+        let rt = runtime();
+        Plugin {
+            module: rt
+                .parse_and_load_module(&COUNTER_WASM[..])
+                .expect("Unable to load module"),
+        }
+    };
+    let bump = plugin
+        .module
+        .find_function::<(), i32>("bump")
+        .expect("Unable to find function");
+    assert_eq!(bump.call(), Ok(6));
+}
+
+#[test]
+fn test_table() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&TABLE_WASM[..])
+        .expect("Unable to load module");
+    let table = module.table().expect("module has a table");
+    assert_eq!(table.capacity(), 3);
+    assert_eq!(table.max(), Some(8));
+    let module = rt
+        .parse_and_load_module(&COUNTER_WASM[..])
+        .expect("Unable to load module");
+    assert!(module.table().is_none());
+}
+
+#[cfg(feature = "wasi")]
+#[test]
+fn test_run_wasi() {
+    let config = wasm3::WasiConfig {
+        args: vec!["prog".to_string(), "a".to_string(), "b".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(wasm3::run_wasi(&EXIT_WITH_ARGC_WASM[..], config), Ok(3));
+    assert_eq!(
+        wasm3::run_wasi(&COMMAND_WASM[..], Default::default()),
+        Ok(0)
+    );
+    assert_eq!(
+        wasm3::run_wasi(&REACTOR_WASM[..], Default::default()),
+        Err(wasm3::error::Error::FunctionNotFound)
+    );
+}
+
+#[test]
+fn test_call_from_memory() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&FIRST_PLUS_LEN_WASM[..])
+        .expect("Unable to load module");
+    let func = module
+        .find_function::<(u32, u32), u32>("first_plus_len")
+        .expect("Unable to find function");
+    unsafe {
+        let memory = &mut *rt.memory_mut();
+        memory[32..36].copy_from_slice(&32u32.to_le_bytes());
+        memory[36..40].copy_from_slice(&10u32.to_le_bytes());
+    }
+    assert_eq!(func.call_from_memory(32), Ok(42));
+    assert_eq!(
+        func.call_from_memory(65532),
+        Err(wasm3::error::Error::MemoryOutOfBounds)
+    );
+}
+
+#[test]
+fn test_wasm_callable() {
+    fn bump_twice(bump: &impl wasm3::WasmCallable<(), i32>) -> wasm3::error::Result<i32> {
+        bump.invoke(())?;
+        bump.invoke(())
+    }
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&COUNTER_WASM[..])
+        .expect("Unable to load module");
+    let bump = module
+        .find_function::<(), i32>("bump")
+        .expect("Unable to find function");
+    assert_eq!(bump_twice(&bump), Ok(7));
+    let counter = Cell::new(0);
+    let mock = |()| Ok(counter.replace(counter.get() + 1) + 1);
+    assert_eq!(bump_twice(&mock), Ok(2));
+}
+
+#[test]
+fn test_snapshot_diff() {
+    let rt = runtime();
+    rt.parse_and_load_module(&IDENTITY_WASM[..])
+        .expect("Unable to load module");
+    let base = rt.snapshot();
+    assert!(rt.snapshot_diff(&base).is_empty());
+    unsafe {
+        let memory = &mut *rt.memory_mut();
+        memory[16] = b'j';
+        memory[10_000] = 1;
+    }
+    let diff = rt.snapshot_diff(&base);
+    let offsets: Vec<_> = diff.chunks().map(|(offset, _)| offset).collect();
+    assert_eq!(offsets, [0, 8192]);
+    let changed = rt.snapshot();
+
+    rt.restore_snapshot(&base)
+        .expect("Unable to restore snapshot");
+    assert_eq!(unsafe { &(&*rt.memory())[16..21] }, b"hello");
+    rt.restore_diff(&base, &diff)
+        .expect("Unable to restore diff");
+    assert_eq!(rt.snapshot(), changed);
+}
+
+#[test]
+fn test_function_signature() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&FIRST_PLUS_LEN_WASM[..])
+        .expect("Unable to load module");
+    let func = module
+        .find_function_dynamic("first_plus_len")
+        .expect("Unable to find function");
+    assert_eq!(
+        func.signature(),
+        Signature {
+            args: vec![ValueType::I32, ValueType::I32],
+            ret: Some(ValueType::I32)
+        }
+    );
+    assert_eq!(
+        module.function_signatures().next().map(|(_, sig)| sig),
+        Some(func.signature())
+    );
+}
+
+#[test]
+fn test_nan_round_trip() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&FLOAT_IDENTITY_WASM[..])
+        .expect("Unable to load module");
+    let f32_identity = module
+        .find_function::<f32, f32>("identity_f32")
+        .expect("Unable to find function");
+    let f64_identity = module
+        .find_function::<f64, f64>("identity_f64")
+        .expect("Unable to find function");
+    let f64_dynamic = module
+        .find_function_dynamic("identity_f64")
+        .expect("Unable to find function");
+
+    // quiet, signaling, with payloads, and negative
+    for &bits in &[
+        0x7FC0_0000u32,
+        0x7F80_0001,
+        0x7FA5_A5A5,
+        0xFFC0_0001,
+        0xFFBF_FFFF,
+    ] {
+        let ret = f32_identity.call(f32::from_bits(bits)).unwrap();
+        assert_eq!(ret.to_bits(), bits);
+    }
+    for &bits in &[
+        0x7FF8_0000_0000_0000u64,
+        0x7FF0_0000_0000_0001,
+        0x7FF4_DEAD_BEEF_0000,
+        0xFFF8_0000_0000_0001,
+        0xFFF7_FFFF_FFFF_FFFF,
+    ] {
+        let ret = f64_identity.call(f64::from_bits(bits)).unwrap();
+        assert_eq!(ret.to_bits(), bits);
+        match f64_dynamic.call_dynamic(&[Value::F64(f64::from_bits(bits))]) {
+            Ok(Some(Value::F64(ret))) => assert_eq!(ret.to_bits(), bits),
+            ret => panic!("unexpected result {:?}", ret),
+        }
+    }
+}
+
+#[test]
+fn test_compiled_function_names() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&CALLS_WASM[..])
+        .expect("Unable to load module");
+    assert_eq!(module.compiled_function_names().count(), 0);
+    let main = module
+        .find_function::<(), ()>("main")
+        .expect("Unable to find function");
+    assert_eq!(
+        module.compiled_function_names().collect::<Vec<_>>(),
+        ["main"]
+    );
+    main.call().unwrap();
+    assert_eq!(
+        module.compiled_function_names().collect::<Vec<_>>(),
+        ["a", "main"]
+    );
+}
+
+#[test]
+fn test_memory_cow() {
+    let rt = runtime();
+    rt.parse_and_load_module(&IDENTITY_WASM[..])
+        .expect("Unable to load module");
+    let copy = rt.memory_cow(None);
+    assert!(matches!(copy, std::borrow::Cow::Owned(_)));
+    assert_eq!(&copy[16..21], b"hello");
+    let guard = rt.guard_memory().expect("Unable to guard memory");
+    let borrowed = rt.memory_cow(Some(&guard));
+    assert!(matches!(borrowed, std::borrow::Cow::Borrowed(_)));
+    assert_eq!(borrowed, copy);
+    let used = rt.memory_cow(Some(&guard));
+    assert!(matches!(used, std::borrow::Cow::Owned(_)));
+    assert_eq!(used, copy);
+}
+
+#[test]
+fn test_memory_cow_uses_guard() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&IDENTITY_WASM[..])
+        .expect("Unable to load module");
+    let func = module
+        .find_function::<u64, u64>("identity")
+        .expect("Unable to find function");
+    let guard = rt.guard_memory().expect("Unable to guard memory");
+    let borrowed = rt.memory_cow(Some(&guard));
+    assert_eq!(
+        func.call_returning_str_borrowed(16 | 5 << 32, &guard),
+        Err(wasm3::error::Error::MemoryGuarded)
+    );
+    assert_eq!(&borrowed[16..21], b"hello");
+}
+
+#[test]
+fn test_link_functions() {
+    let rt = runtime();
+    let mut module = rt
+        .parse_and_load_module(&ADD_AND_LOG_WASM[..])
+        .expect("Unable to load module");
+    let logged = Rc::new(Cell::new(0));
+    let log = {
+        let logged = logged.clone();
+        move |_: CallContext, val: i32| logged.set(val)
+    };
+    wasm3::link_functions!(module, "env", {
+        "add" => |a: i32, b: i32| a + b,
+        "log" => log,
+    })
+    .expect("Unable to link functions");
+    let run = module
+        .find_function::<(), i32>("run")
+        .expect("Unable to find function");
+    assert_eq!(run.call(), Ok(5));
+    assert_eq!(logged.get(), 7);
+
+    let mut module = rt
+        .parse_and_load_module(&ADD_AND_LOG_WASM[..])
+        .expect("Unable to load module");
+    assert_eq!(
+        wasm3::link_functions!(module, "env", { "add" => |a: i64| a }),
+        Err(wasm3::error::Error::InvalidFunctionSignature)
+    );
+    let log = logged.clone();
+    wasm3::link_functions!(module, "env", {
+        "add" => move |a: i32, b: i32| -> i32 { a * b },
+        "log" => move |val: i32| log.set(val * 2),
+    })
+    .expect("Unable to link functions");
+    let run = module
+        .find_function::<(), i32>("run")
+        .expect("Unable to find function");
+    assert_eq!(run.call(), Ok(6));
+    assert_eq!(logged.get(), 14);
+
+    let millis = || {
+        rt.parse_and_load_module(
+            &include_bytes!("../examples/wasm/wasm_millis_to_seconds/wasm_millis_to_seconds.wasm")
+                [..],
+        )
+        .expect("Unable to load module")
+    };
+    let seconds = |module: &Module| {
+        module
+            .find_function::<(), u64>("seconds")
+            .expect("Unable to find function")
+            .call()
+    };
+    let mut module = millis();
+    wasm3::link_functions!(module, "time", { "millis" => || 5000u64 })
+        .expect("Unable to link functions");
+    assert_eq!(seconds(&module), Ok(5));
+    let offset = 2000;
+    let mut module = millis();
+    wasm3::link_functions!(module, "time", { "millis" => move || -> u64 { 7000 - offset } })
+        .expect("Unable to link functions");
+    assert_eq!(seconds(&module), Ok(5));
+}
+
+#[test]
+fn test_can_load() {
+    let env = Environment::new().expect("Unable to create environment");
+    let rt = env
+        .create_runtime(1024 * 60)
+        .expect("Unable to create runtime");
+    let module = Module::parse(&env, &POST_MVP_WASM[..]).expect("Unable to parse module");
+    assert_eq!(
+        rt.can_load(&module),
+        Err(vec![
+            UnsupportedFeature::BulkMemory,
+            UnsupportedFeature::TailCall
+        ])
+    );
+    let module = Module::parse(&env, &CALLS_WASM[..]).expect("Unable to parse module");
+    assert_eq!(rt.can_load(&module), Ok(()));
+}
+
+#[test]
+fn test_memory_info() {
+    let rt = runtime();
+    rt.parse_and_load_module(&IDENTITY_WASM[..])
+        .expect("Unable to load module");
+    let info = rt.memory_info();
+    assert_eq!(info.size, 65536);
+    assert_eq!(info.num_pages, 1);
+    assert_eq!(info.max_pages, 65536);
+    rt.resize_memory(2).expect("Unable to resize memory");
+    assert_eq!(rt.memory_info().num_pages, 2);
+    assert_eq!(rt.memory_info().size, 2 * 65536);
+}
+
+#[test]
+fn test_call_stream() {
+    let rt = runtime();
+    let module = rt
+        .parse_and_load_module(&FIRST_PLUS_LEN_WASM[..])
+        .expect("Unable to load module");
+    let func = module
+        .find_function::<(u32, u32), u32>("first_plus_len")
+        .expect("Unable to find function");
+    let results: Vec<_> = func
+        .call_stream(vec![(0, 1), (u32::MAX, 0), (0, 2)].into_iter())
+        .collect();
+    assert_eq!(results[0], Ok(1));
+    assert!(results[1].is_err());
+    assert_eq!(results[2], Ok(2));
+}
+
+#[test]
+fn test_validate() {
+    let env = Environment::new().expect("Unable to create environment");
+    let module = Module::parse(&env, &ADD_AND_LOG_WASM[..]).expect("Unable to parse module");
+    assert_eq!(module.validate(), Ok(()));
+    let module = Module::parse(&env, &STACK_UNDERRUN_WASM[..]).expect("Unable to parse module");
+    match module.validate() {
+        Err(wasm3::error::Error::Validation { function_index, .. }) => {
+            assert_eq!(function_index, 1)
+        }
+        res => panic!("unexpected result {:?}", res),
+    }
+    // the start function calls an unlinked import and never returns, so it must not run
+    let module = Module::parse(&env, &START_LOOP_WASM[..]).expect("Unable to parse module");
+    assert_eq!(module.validate(), Ok(()));
+}
+
+#[test]
+fn test_resolve_imports() {
+    wasm3::make_func_wrapper!(log_wrap: log(_val: i32));
+    fn log(_val: i32) {}
+
+    let rt = runtime();
+    rt.link_default_closure("env_v2", "add", |_, (a, b): (i32, i32)| a + b);
+    let mut module = rt
+        .parse_and_load_module(&ADD_AND_LOG_WASM[..])
+        .expect("Unable to load module");
+    let mut seen = Vec::new();
+    module
+        .resolve_imports(|module_name, name| {
+            seen.push(format!("{}.{}", module_name, name));
+            match name {
+                "add" => Some(wasm3::LinkTarget::Default {
+                    module_name: format!("{}_v2", module_name),
+                    function_name: name.to_string(),
+                }),
+                _ => None,
+            }
+        })
+        .expect("Unable to resolve imports");
+    assert_eq!(seen, ["env.add", "env.log"]);
+    let raw = |args| wasm3::LinkTarget::Raw {
+        function: log_wrap,
+        signature: Signature { args, ret: None },
+    };
+    assert_eq!(
+        module.resolve_imports(|_, _| Some(raw(vec![ValueType::I64]))),
+        Err(wasm3::error::Error::InvalidFunctionSignature)
+    );
+    module
+        .resolve_imports(|_, _| Some(raw(vec![ValueType::I32])))
+        .expect("Unable to resolve imports");
+    let run = module
+        .find_function::<(), i32>("run")
+        .expect("Unable to find function");
+    assert_eq!(run.call(), Ok(5));
+
+    let mut module = rt
+        .parse_and_load_module(&ADD_AND_LOG_WASM[..])
+        .expect("Unable to load module");
+    assert_eq!(
+        module.resolve_imports(|_, _| Some(wasm3::LinkTarget::Default {
+            module_name: "missing".to_string(),
+            function_name: "add".to_string(),
+        })),
+        Err(wasm3::error::Error::FunctionNotFound)
+    );
+}
+
+#[test]
+fn test_debug_dump() {
+    let env = Environment::new().expect("Unable to create environment");
+    let module = Module::parse(&env, &ADD_AND_LOG_WASM[..]).expect("Unable to parse module");
+    let dump = module.debug_dump();
+    assert!(
+        dump.contains("  env.add: function (i32, i32) -> i32\n"),
+        "{}",
+        dump
+    );
+    assert!(dump.contains("  env.log: function (i32)\n"), "{}", dump);
+    assert!(dump.contains("  run: function () -> i32\n"), "{}", dump);
+    assert!(dump.contains("data segments: 0\n"), "{}", dump);
+
+    let module = Module::parse(&env, &LAYOUT_WASM[..]).expect("Unable to parse module");
+    let dump = module.debug_dump();
+    assert!(dump.contains("memory: 1 pages"), "{}", dump);
+    assert!(dump.contains("data segments: 1\n"), "{}", dump);
+}
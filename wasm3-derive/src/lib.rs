@@ -0,0 +1,162 @@
+#![warn(missing_docs)]
+//! Derive macros for [wasm3](https://crates.io/crates/wasm3).
+
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+/// Derives `WasmType` and `WasmArg` for a `#[repr(transparent)]` newtype around one of the
+/// primitive wasm types, allowing it to be used in function signatures in place of the primitive.
+///
+/// The struct has to have exactly one field, which is the one passed to and from wasm.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(wasm3::WasmType)]
+/// #[repr(transparent)]
+/// struct GuestPtr(u32);
+/// ```
+#[proc_macro_derive(WasmType)]
+pub fn derive_wasm_type(input: TokenStream) -> TokenStream {
+    match Newtype::parse(input) {
+        Ok(newtype) => newtype.expand(),
+        Err(msg) => format!("compile_error!({:?});", msg),
+    }
+    .parse()
+    .unwrap()
+}
+
+const EXACTLY_ONE_FIELD: &str = "WasmType can only be derived for structs with exactly one field";
+
+struct Newtype {
+    name: String,
+    // the field's name for structs with named fields, `0` for tuple structs
+    field: String,
+    ty: String,
+}
+
+impl Newtype {
+    fn parse(input: TokenStream) -> Result<Self, &'static str> {
+        let mut tokens = input.into_iter().peekable();
+        let mut transparent = false;
+        while let Some(TokenTree::Punct(punct)) = tokens.peek() {
+            if punct.as_char() != '#' {
+                break;
+            }
+            tokens.next();
+            if let Some(TokenTree::Group(group)) = tokens.next() {
+                let attr = group.stream().to_string().replace(' ', "");
+                transparent |= attr == "repr(transparent)";
+            }
+        }
+        if !transparent {
+            return Err("WasmType can only be derived for `#[repr(transparent)]` structs");
+        }
+        skip_visibility(&mut tokens);
+        match tokens.next() {
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "struct" => (),
+            _ => return Err("WasmType can only be derived for structs"),
+        }
+        let name = match tokens.next() {
+            Some(TokenTree::Ident(ident)) => ident.to_string(),
+            _ => return Err("expected a struct name"),
+        };
+        let body = match tokens.next() {
+            Some(TokenTree::Group(group)) => group,
+            _ => return Err("WasmType can not be derived for generic or unit structs"),
+        };
+        let mut fields = body.stream().into_iter().peekable();
+        if fields.peek().is_none() {
+            return Err(EXACTLY_ONE_FIELD);
+        }
+        // skip field attributes
+        while let Some(TokenTree::Punct(punct)) = fields.peek() {
+            if punct.as_char() != '#' {
+                break;
+            }
+            fields.next();
+            fields.next();
+        }
+        skip_visibility(&mut fields);
+        let field = match body.delimiter() {
+            Delimiter::Parenthesis => "0".to_string(),
+            Delimiter::Brace => {
+                let field = match fields.next() {
+                    Some(TokenTree::Ident(ident)) => ident.to_string(),
+                    _ => return Err("expected a field name"),
+                };
+                match fields.next() {
+                    Some(TokenTree::Punct(punct)) if punct.as_char() == ':' => field,
+                    _ => return Err("expected a field type"),
+                }
+            }
+            _ => return Err("WasmType can only be derived for structs"),
+        };
+        // the type ends at the first comma outside of generic arguments
+        let mut ty = Vec::new();
+        let mut depth = 0usize;
+        let mut arrow = false;
+        for token in &mut fields {
+            if let TokenTree::Punct(punct) = &token {
+                match punct.as_char() {
+                    ',' if depth == 0 => break,
+                    '<' => depth += 1,
+                    '>' if !arrow => depth = depth.saturating_sub(1),
+                    _ => (),
+                }
+                arrow = punct.as_char() == '-';
+            } else {
+                arrow = false;
+            }
+            ty.push(token);
+        }
+        if ty.is_empty() || fields.next().is_some() {
+            return Err(EXACTLY_ONE_FIELD);
+        }
+        Ok(Newtype {
+            name,
+            field,
+            ty: ty.into_iter().collect::<TokenStream>().to_string(),
+        })
+    }
+
+    fn expand(&self) -> String {
+        let Newtype { name, field, ty } = self;
+        let construct = if field == "0" {
+            format!("{}(val)", name)
+        } else {
+            format!("{} {{ {}: val }}", name, field)
+        };
+        format!(
+            r#"
+            impl ::wasm3::__private::Newtype for {name} {{
+                type Inner = {ty};
+                fn from_inner(val: {ty}) -> Self {{
+                    {construct}
+                }}
+                fn into_inner(self) -> {ty} {{
+                    self.{field}
+                }}
+            }}
+            "#,
+            name = name,
+            ty = ty,
+            field = field,
+            construct = construct,
+        )
+    }
+}
+
+fn skip_visibility(tokens: &mut core::iter::Peekable<impl Iterator<Item = TokenTree>>) {
+    if let Some(TokenTree::Ident(ident)) = tokens.peek() {
+        if ident.to_string() == "pub" {
+            tokens.next();
+            if let Some(TokenTree::Group(group)) = tokens.peek() {
+                if group.delimiter() == Delimiter::Parenthesis {
+                    tokens.next();
+                }
+            }
+        }
+    }
+}